@@ -0,0 +1,210 @@
+use rust_raytracingv2::objects::{sphere::Sphere, moving_sphere::MovingSphere, cube::Cube};
+use rust_raytracingv2::material::lambertian::Lambertian;
+use rust_raytracingv2::material::material::Material;
+use rust_raytracingv2::textures::ConstantTexture;
+use rust_raytracingv2::hittable::Hittable;
+use rust_raytracingv2::hittable::bounding::BvhNode;
+use rust_raytracingv2::hittable::linear::LinearBvh;
+use rust_raytracingv2::hittable::qbvh::Qbvh;
+use rust_raytracingv2::hittable::accelerator::Accelerator;
+use rust_raytracingv2::utils::color::Color;
+use rust_raytracingv2::utils::vec3::Vec3;
+use rust_raytracingv2::utils::ray::Ray;
+use rust_raytracingv2::utils::Float;
+
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+const RAYS: usize = 1_000_000;
+//primary rays are traced in 8x8 tiles so a packet covers neighbouring pixels
+const TILE: usize = 8;
+const RESOLUTION: usize = 1024;
+
+fn random_spheres(rng: &mut StdRng, material: Rc<dyn Material>) -> Vec<Rc<dyn Hittable>> {
+    let mut world: Vec<Rc<dyn Hittable>> = vec![];
+
+    for a in -10..10 {
+        for b in -10..10 {
+            let center = Vec3::new(a as Float + 0.9 * rng.gen_range(0.0, 1.0), 0.2, b as Float + 0.9 * rng.gen_range(0.0, 1.0));
+
+            if rng.gen_range(0.0, 1.0) < 0.8 {
+                let end = center + Vec3::new(0.0, rng.gen_range(0.0, 0.5), 0.0);
+                world.push(Rc::new(MovingSphere::new(center, end, 0.0, 1.0, 0.2, material.clone())));
+            }
+            else {
+                world.push(Rc::new(Sphere::new(center, 0.2, material.clone())));
+            }
+        }
+    }
+
+    world.push(Rc::new(Sphere::new(Vec3::new(0.0, 1.0, 0.0), 1.0, material.clone())));
+    world.push(Rc::new(Sphere::new(Vec3::new(-4.0, 1.0, 0.0), 1.0, material.clone())));
+    world.push(Rc::new(Sphere::new(Vec3::new(4.0, 1.0, 0.0), 1.0, material)));
+    world
+}
+
+fn floor_and_spheres(rng: &mut StdRng, material: Rc<dyn Material>) -> Vec<Rc<dyn Hittable>> {
+    let mut world: Vec<Rc<dyn Hittable>> = vec![];
+
+    for i in 0..20 {
+        for j in 0..20 {
+            let min = Vec3::new(-1000.0 + i as Float * 100.0, 0.0, -1000.0 + j as Float * 100.0);
+            let max = min + Vec3::new(100.0, rng.gen_range(1.0, 100.0), 100.0);
+
+            world.push(Rc::new(Cube::from_vertices(min, max, material.clone())));
+        }
+    }
+
+    for _ in 0..1000 {
+        let center = Vec3::new(rng.gen_range(0.0, 165.0), rng.gen_range(0.0, 165.0), rng.gen_range(0.0, 165.0));
+        world.push(Rc::new(Sphere::new(center + Vec3::new(-100.0, 270.0, 395.0), 10.0, material.clone())));
+    }
+
+    world
+}
+
+//large enough that the nodes no longer fit in the cache, where fetching a node once for a whole packet pays off
+fn dust(rng: &mut StdRng, material: Rc<dyn Material>) -> Vec<Rc<dyn Hittable>> {
+    (0..100_000)
+        .map(|_| {
+            let center = Vec3::new(rng.gen_range(-100.0, 100.0), rng.gen_range(-100.0, 100.0), rng.gen_range(-100.0, 100.0));
+            Rc::new(Sphere::new(center, 0.3, material.clone())) as Rc<dyn Hittable>
+        })
+        .collect()
+}
+
+fn rays(rng: &mut StdRng, from: Vec3, target: Vec3, spread: Float) -> Vec<Ray> {
+    (0..RAYS)
+        .map(|_| {
+            let offset = Vec3::new(rng.gen_range(-spread, spread), rng.gen_range(-spread, spread), rng.gen_range(-spread, spread));
+            Ray::new(from, target + offset - from, rng.gen_range(0.0, 1.0))
+        })
+        .collect()
+}
+
+fn primary_rays(from: Vec3, target: Vec3, spread: Float) -> Vec<Ray> {
+    let forward = (target - from).unit_vector();
+    let right = Vec3::cross_product(forward, Vec3::new(0.0, 1.0, 0.0)).unit_vector();
+    let up = Vec3::cross_product(right, forward);
+    let mut result = Vec::with_capacity(RESOLUTION * RESOLUTION);
+
+    for tile_y in (0..RESOLUTION).step_by(TILE) {
+        for tile_x in (0..RESOLUTION).step_by(TILE) {
+            for y in tile_y..tile_y + TILE {
+                for x in tile_x..tile_x + TILE {
+                    let u = (x as Float / RESOLUTION as Float - 0.5) * 2.0 * spread;
+                    let v = (y as Float / RESOLUTION as Float - 0.5) * 2.0 * spread;
+                    result.push(Ray::new(from, target + right * u + up * v - from, 0.5));
+                }
+            }
+        }
+    }
+
+    result
+}
+
+fn trace(name: &str, size: usize, accelerator: &dyn Hittable, build: Duration, rays: &[Ray]) {
+    let start = Instant::now();
+    let hits = rays.iter().filter(|ray| accelerator.hit(ray, 0.001, Float::MAX).is_some()).count();
+    let trace = start.elapsed();
+
+    println!(
+        "{:<20} {:>6} objects  build {:>8.2?}  trace {:>8.2?}  {:>6.2} Mrays/s  {} hits",
+        name, size, build, trace, rays.len() as f64 / trace.as_secs_f64() / 1e6, hits
+    );
+}
+
+fn trace_packets(name: &str, size: usize, accelerator: &Qbvh, build: Duration, rays: &[Ray]) {
+    let start = Instant::now();
+    let hits = accelerator.hit_packet(rays, 0.001, Float::MAX).iter().filter(|record| record.is_some()).count();
+    let trace = start.elapsed();
+
+    println!(
+        "{:<20} {:>6} objects  build {:>8.2?}  trace {:>8.2?}  {:>6.2} Mrays/s  {} hits",
+        name, size, build, trace, rays.len() as f64 / trace.as_secs_f64() / 1e6, hits
+    );
+}
+
+fn bench(name: &str, mut objects: Vec<Rc<dyn Hittable>>, rays: &[Ray], primary: &[Ray]) {
+    let size = objects.len();
+
+    let start = Instant::now();
+    let linear = LinearBvh::new(&objects, 0.0, 1.0);
+    let linear_build = start.elapsed();
+
+    let start = Instant::now();
+    let qbvh = Qbvh::new(&objects, 0.0, 1.0);
+    let qbvh_build = start.elapsed();
+
+    let start = Instant::now();
+    let bvh = BvhNode::new(&mut objects, 0, size, 0.0, 1.0);
+    let bvh_build = start.elapsed();
+
+    trace(&format!("{} BvhNode", name), size, &bvh, bvh_build, rays);
+    trace(&format!("{} LinearBvh", name), size, &linear, linear_build, rays);
+    trace(&format!("{} Qbvh", name), size, &qbvh, qbvh_build, rays);
+
+    for accelerator in [Accelerator::UniformGrid, Accelerator::KdTree] {
+        let start = Instant::now();
+        let built = accelerator.build(&objects, 0.0, 1.0);
+        let build = start.elapsed();
+
+        trace(&format!("{} {:?}", name, accelerator), size, built.as_ref(), build, rays);
+    }
+
+    trace(&format!("{} primary Linear", name), size, &linear, linear_build, primary);
+    trace(&format!("{} primary Qbvh", name), size, &qbvh, qbvh_build, primary);
+    trace_packets(&format!("{} primary packet", name), size, &qbvh, qbvh_build, primary);
+}
+
+fn build_stats(name: &str, objects: &[Rc<dyn Hittable>]) {
+    let available = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+
+    for threads in if available > 1 { vec![1, available] } else { vec![1] } {
+        let stats = LinearBvh::with_threads(objects, 0.0, 1.0, threads).stats();
+
+        println!(
+            "{:<20} {:>6} objects  build {:>8.2?} on {} threads  {} nodes  {} leaves  depth {}  {:.2} per leaf  cost {:.2}",
+            name, objects.len(), stats.time, stats.threads, stats.nodes, stats.leaves, stats.max_depth, stats.average_leaf_size, stats.cost
+        );
+    }
+}
+
+fn cache(name: &str, objects: &[Rc<dyn Hittable>]) {
+    let path = std::env::temp_dir().join("bvh_bench.bvh");
+    let _ = std::fs::remove_file(&path);
+
+    for run in ["cold", "warm"] {
+        let start = Instant::now();
+        LinearBvh::cached(&path, objects, 0.0, 1.0).expect("Could not write the BVH cache");
+        println!("{:<20} {:>6} objects  {} cache {:>8.2?}", name, objects.len(), run, start.elapsed());
+    }
+
+    let _ = std::fs::remove_file(&path);
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(42);
+    let material: Rc<dyn Material> = Rc::new(Lambertian::new(Rc::new(ConstantTexture::new(Color::new(0.5, 0.5, 0.5)))));
+
+    let random = random_spheres(&mut rng, material.clone());
+    let random_rays = rays(&mut rng, Vec3::new(13.0, 2.0, 3.0), Vec3::new(0.0, 0.0, 0.0), 6.0);
+    let random_primary = primary_rays(Vec3::new(13.0, 2.0, 3.0), Vec3::new(0.0, 0.0, 0.0), 3.0);
+    bench("random", random, &random_rays, &random_primary);
+
+    let floor = floor_and_spheres(&mut rng, material.clone());
+    let floor_rays = rays(&mut rng, Vec3::new(478.0, 278.0, -600.0), Vec3::new(278.0, 278.0, 0.0), 400.0);
+    let floor_primary = primary_rays(Vec3::new(478.0, 278.0, -600.0), Vec3::new(278.0, 278.0, 0.0), 300.0);
+    bench("test", floor, &floor_rays, &floor_primary);
+
+    let dust = dust(&mut rng, material);
+    let dust_rays = rays(&mut rng, Vec3::new(0.0, 0.0, -300.0), Vec3::new(0.0, 0.0, 0.0), 100.0);
+    let dust_primary = primary_rays(Vec3::new(0.0, 0.0, -300.0), Vec3::new(0.0, 0.0, 0.0), 90.0);
+    //a tenth of the rays, every one of them crosses the whole cloud
+    build_stats("dust LinearBvh", &dust);
+    cache("dust LinearBvh", &dust);
+    bench("dust", dust, &dust_rays[..RAYS / 10], &dust_primary[..RAYS / 10]);
+}
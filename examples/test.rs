@@ -1,18 +1,20 @@
 #![allow(unused_imports)]
 #![allow(dead_code)]
 
-use rust_raytracingv2::objects::{sphere::Sphere, moving_sphere::MovingSphere, rectangles::*, cube::Cube, constant_medium::ConstantMedium};
+use rust_raytracingv2::objects::{sphere::Sphere, moving_sphere::MovingSphere, rectangles::*, cube::Cube, constant_medium::ConstantMedium, plane::Plane};
 use rust_raytracingv2::material::{light::Light, dielectric::Dielectric, lambertian::Lambertian, metal::Metal};
 use rust_raytracingv2::textures::{CheckerTexture, ConstantTexture, NoiseTexture, ImageTexture};
 use rust_raytracingv2::hittable::{Hittable, HittableList, FlipFace, RotateY, Translate};
 use rust_raytracingv2::hittable::bounding::BvhNode;
+use rust_raytracingv2::hittable::instance::Instance;
 use rust_raytracingv2::camera::camera::Camera;
 use rust_raytracingv2::utils::color::Color;
 use rust_raytracingv2::utils::vec3::Vec3;
+use rust_raytracingv2::utils::quaternion::Quaternion;
 use rust_raytracingv2::utils::ray::Ray;
+use rust_raytracingv2::utils::Float;
 
 use std::io;
-use std::f64;
 use std::rc::Rc;
 use std::io::Write;
 
@@ -26,9 +28,9 @@ fn random_scene() -> Vec<Rc<dyn Hittable>> {
     let mut rng = rand::thread_rng();
     let range = Uniform::from(0.0..1.0);
 
-    world.push(Rc::new(Sphere::new(
-        Vec3::new(0.0, -1000.0, 0.0),
-        1000.0,
+    world.push(Rc::new(Plane::new(
+        Vec3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
         Rc::new(Lambertian::new(Rc::new(
             CheckerTexture::new(
                 Rc::new(ConstantTexture::new(Color::new(0.2, 0.3, 0.1))),
@@ -41,9 +43,9 @@ fn random_scene() -> Vec<Rc<dyn Hittable>> {
         for b in -10..10 {
             let mat = range.sample(&mut rng);
             let center = Vec3::new(
-                a as f64 + 0.9 * range.sample(&mut rng),
+                a as Float + 0.9 * range.sample(&mut rng),
                 0.2,
-                b as f64 + 0.9 * range.sample(&mut rng),
+                b as Float + 0.9 * range.sample(&mut rng),
             );
 
             if (center - Vec3::new(4.0, 0.2, 0.0)).length() > 0.9 {
@@ -160,7 +162,7 @@ fn cornell_box() -> Vec<Rc<dyn Hittable>> {
     objects
 }
 
-fn test_scene() -> Vec<Rc<dyn Hittable>> {
+fn test_scene() -> (Vec<Rc<dyn Hittable>>, Rc<dyn Hittable>) {
     let mut floor: Vec<Rc<dyn Hittable>> = vec![];
     let mut scene: Vec<Rc<dyn Hittable>> = vec![];
     let mut objects: Vec<Rc<dyn Hittable>> = vec![];
@@ -176,8 +178,8 @@ fn test_scene() -> Vec<Rc<dyn Hittable>> {
     for i in 0..boxes {
         for j in 0..boxes {
             let w = 100.0;
-            let x0 = -1000.0 + i as f64 * w;
-            let z0 = -1000.0 + j as f64 * w;
+            let x0 = -1000.0 + i as Float * w;
+            let z0 = -1000.0 + j as Float * w;
             let y0 = 0.0;
             let x1 = x0 + w;
             let z1 = z0 + w;
@@ -188,7 +190,8 @@ fn test_scene() -> Vec<Rc<dyn Hittable>> {
     }
     
     let light = Rc::new(Light::new(Rc::new(ConstantTexture::new(Color::new(7.0, 7.0, 7.0)))));
-    objects.push(Rc::new(XZRectangle::new(123.0, 423.0, 147.0, 412.0, 554.0, light.clone())));
+    let lamp: Rc<dyn Hittable> = Rc::new(XZRectangle::new(123.0, 423.0, 147.0, 412.0, 554.0, light.clone()));
+    objects.push(lamp.clone());
     
 
     let center1 = Vec3::new(400.0, 400.0, 200.0);
@@ -224,9 +227,11 @@ fn test_scene() -> Vec<Rc<dyn Hittable>> {
         boxes2.push(Rc::new(Sphere::new(Vec3::random_range(0.0, 165.0), 10.0, white.clone())));
     }
     
-    objects.push(Rc::new(
-        Translate::new(Rc::new(RotateY::new(Rc::new(BvhNode::new(&mut boxes2, 0, cont, 0.0, 1.0)), 15.0)), 
-        Vec3::new(-100.0, 270.0, 395.0)
+    objects.push(Rc::new(Instance::new(
+        Rc::new(BvhNode::new(&mut boxes2, 0, cont, 0.0, 1.0)),
+        Vec3::new(-100.0, 270.0, 395.0),
+        Quaternion::from_axis_angle(Vec3::new(0.0, 1.0, 0.0), 15.0),
+        Vec3::new(1.0, 1.0, 1.0)
     )));
     
     let sz_floor = floor.len();
@@ -234,22 +239,37 @@ fn test_scene() -> Vec<Rc<dyn Hittable>> {
     scene.push(Rc::new(BvhNode::new(&mut floor, 0, sz_floor, 0.0, 0.1)));
     scene.push(Rc::new(BvhNode::new(&mut objects, 0, sz_objects, 0.0, 0.1)));
         
-    scene
+    (scene, lamp)
 }
 
-fn get_color(ray: &Ray, background: &Color, world: Rc<dyn Hittable>, depth: i32) -> Color {
+fn get_color(ray: &Ray, background: &Color, world: Rc<dyn Hittable>, lights: Rc<dyn Hittable>, depth: i32) -> Color {
     if depth <= 0 {
         return Color::default();
     }
 
-    if let Some(record) = world.hit(ray, 0.001, f64::MAX) {
+    if let Some(record) = world.hit(ray, 0.001, Float::MAX) {
         let emitted = record.material.emit(record.u, record.v, &record.point);
         
-        if let Some(scatter) = record.material.scatter(ray, &record) {
-            return emitted + get_color(&scatter.ray, background, world, depth - 1) * scatter.attenuation;
+        let scatter = match record.material.scatter(ray, &record) {
+            Some(scatter) => scatter,
+            None => return emitted
+        };
+
+        //mirrors and glass keep their own ray, the rest aim half of their rays at the lights
+        if record.material.scattering_pdf(ray, &record, &scatter.ray) <= 0.0 {
+            return emitted + get_color(&scatter.ray, background, world, lights, depth - 1) * scatter.attenuation;
+        }
+
+        let direction = if rand::thread_rng().gen::<bool>() { lights.random(&record.point) } else { scatter.ray.direction };
+        let scattered = Ray::new(record.point, direction, ray.time);
+        let scattering_pdf = record.material.scattering_pdf(ray, &record, &scattered);
+        let pdf = 0.5 * lights.pdf_value(&record.point, &direction) + 0.5 * scattering_pdf;
+
+        if pdf <= 0.0 {
+            return emitted;
         }
 
-        return emitted;
+        return emitted + get_color(&scattered, background, world, lights, depth - 1) * scatter.attenuation * (scattering_pdf / pdf);
     }
 
     *background
@@ -273,14 +293,14 @@ fn main() {
         look_at,
         Vec3::new(0.0, 1.0, 0.0),
         40.0,
-        (width as f64) / (height as f64),
+        (width as Float) / (height as Float),
         aperture,
         dist_to_focus,
         0.0,
         1.0
     );
 
-    let mut world = test_scene();
+    let (mut world, lights) = test_scene();
     let sz = world.len();
 
     let world = Rc::new(BvhNode::new(&mut world, 0, sz, 0.0, 1.0));
@@ -295,11 +315,11 @@ fn main() {
         for col in 0..width {
             let mut color = Color::default();
             for _ in 0..samples {
-                let x = (col as f64 + range.sample(&mut rng)) / width as f64;
-                let y = (row as f64 + range.sample(&mut rng)) / height as f64;
+                let x = (col as Float + range.sample(&mut rng)) / width as Float;
+                let y = (row as Float + range.sample(&mut rng)) / height as Float;
                 
                 let ray = camera.get_ray(x, y);
-                color = color + get_color(&ray, &background, world.clone(), depth);
+                color = color + get_color(&ray, &background, world.clone(), lights.clone(), depth);
             }
             pixels.push(color);
         }
@@ -308,10 +328,8 @@ fn main() {
 
     let mut buffer = image::ImageBuffer::new(width as u32, height as u32);
 
-    let mut idx = 0;
-    for (_, _, px) in buffer.enumerate_pixels_mut() {
+    for (idx, (_, _, px)) in buffer.enumerate_pixels_mut().enumerate() {
         let (r, g, b) = pixels[idx].to_rgb_with_samples(samples as i32);
-        idx += 1;
 
         *px = image::Rgb([r, g, b]);
     }
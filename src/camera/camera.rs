@@ -1,4 +1,5 @@
 use super::{Ray, Vec3};
+use super::Float;
 
 use rand::Rng;
 
@@ -9,14 +10,14 @@ pub struct Camera {
     vertical: Vec3,
     u: Vec3,
     v: Vec3,
-    w: Vec3,
-    lens_radius: f64,
-    time0: f64,
-    time1: f64
+    lens_radius: Float,
+    time0: Float,
+    time1: Float
 }
 
 impl Camera {
-    pub fn new(origin: Vec3, look_at: Vec3, up: Vec3, fov: f64, aspect: f64, aperture: f64, focus_dist: f64, time0: f64, time1: f64) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(origin: Vec3, look_at: Vec3, up: Vec3, fov: Float, aspect: Float, aperture: Float, focus_dist: Float, time0: Float, time1: Float) -> Self {
         let theta = fov.to_radians();
         let height = (theta / 2.0).tan();
         let width = aspect * height;
@@ -36,14 +37,13 @@ impl Camera {
             vertical,
             v,
             u,
-            w,
             lens_radius: aperture / 2.0,
             time0,
             time1
         }
     }
 
-    pub fn get_ray(&self, x: f64, y: f64) -> Ray {
+    pub fn get_ray(&self, x: Float, y: Float) -> Ray {
         let rd = Vec3::random_in_unit_disk() * self.lens_radius;
         let offset = self.u * rd.x + self.v * rd.y;
         let mut rng = rand::thread_rng();
@@ -1,4 +1,6 @@
+#[allow(clippy::module_inception)]
 pub mod camera;
 
 use super::utils::vec3::Vec3;
-use super::utils::ray::Ray;
\ No newline at end of file
+use super::utils::ray::Ray;
+use super::utils::Float;
\ No newline at end of file
@@ -0,0 +1,35 @@
+use super::bounding::BvhNode;
+use super::grid::UniformGrid;
+use super::hittable::Hittable;
+use super::kdtree::KdTree;
+use super::linear::LinearBvh;
+use super::qbvh::Qbvh;
+use super::Float;
+
+use std::rc::Rc;
+
+//lets a scene pick the structure that suits it, bvh_bench compares them on the same rays
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Accelerator {
+    Bvh,
+    LinearBvh,
+    Qbvh,
+    UniformGrid,
+    KdTree
+}
+
+impl Accelerator {
+    pub fn build(&self, objects: &[Rc<dyn Hittable>], t0: Float, t1: Float) -> Rc<dyn Hittable> {
+        match self {
+            Accelerator::Bvh => {
+                let mut objects = objects.to_vec();
+                let size = objects.len();
+                Rc::new(BvhNode::new(&mut objects, 0, size, t0, t1))
+            },
+            Accelerator::LinearBvh => Rc::new(LinearBvh::new(objects, t0, t1)),
+            Accelerator::Qbvh => Rc::new(Qbvh::new(objects, t0, t1)),
+            Accelerator::UniformGrid => Rc::new(UniformGrid::new(objects, t0, t1)),
+            Accelerator::KdTree => Rc::new(KdTree::new(objects, t0, t1))
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use super::hittable::{HitRecord, Hittable};
+use super::quaternion::Quaternion;
+use super::bounding::BoundingBox;
+use super::{min_float, max_float};
+use super::vec3::Vec3;
+use super::ray::Ray;
+use super::Float;
+
+use std::rc::Rc;
+
+const BOX_STEPS: usize = 64;
+
+#[derive(Debug, Copy, Clone)]
+pub struct Keyframe {
+    pub time: Float,
+    pub translation: Vec3,
+    pub rotation: Quaternion,
+    pub scale: Vec3,
+}
+
+impl Keyframe {
+    pub fn new(time: Float, translation: Vec3, rotation: Quaternion, scale: Vec3) -> Self {
+        Self {
+            time,
+            translation,
+            rotation: rotation.normalize(),
+            scale
+        }
+    }
+
+    fn apply(&self, point: Vec3) -> Vec3 {
+        self.translation + self.rotation.rotate(point * self.scale)
+    }
+
+    //the direction is not normalized so times along the local ray match the world one
+    fn local_ray(&self, ray: &Ray) -> Ray {
+        let inverse = self.rotation.conjugate();
+
+        Ray::new(
+            inverse.rotate(ray.origin - self.translation) / self.scale,
+            inverse.rotate(ray.direction) / self.scale,
+            ray.time
+        )
+    }
+
+    //hits the object with the ray moved to its local space and brings the record back
+    pub fn hit(&self, object: &dyn Hittable, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
+        object.hit(&self.local_ray(ray), tmin, tmax).map(|mut record| {
+            record.point = self.apply(record.point);
+            record.normal = self.rotation.rotate(record.normal / self.scale).unit_vector();
+            record.geometric_normal = self.rotation.rotate(record.geometric_normal / self.scale).unit_vector();
+            record.dpdu = self.rotation.rotate(record.dpdu * self.scale);
+            record.dpdv = self.rotation.rotate(record.dpdv * self.scale);
+            record
+        })
+    }
+
+    pub fn occluded(&self, object: &dyn Hittable, ray: &Ray, tmin: Float, tmax: Float) -> bool {
+        object.occluded(&self.local_ray(ray), tmin, tmax)
+    }
+
+    //the local density times how much the transform stretches solid angle around the direction
+    pub fn pdf_value(&self, object: &dyn Hittable, origin: &Vec3, direction: &Vec3) -> Float {
+        let local = self.local_ray(&Ray::new(*origin, *direction, 0.0));
+        let determinant = (self.scale.x * self.scale.y * self.scale.z).abs();
+        let stretch = direction.length() / local.direction.length();
+
+        object.pdf_value(&local.origin, &local.direction) * stretch * stretch * stretch / determinant
+    }
+
+    pub fn random(&self, object: &dyn Hittable, origin: &Vec3) -> Vec3 {
+        let local = self.local_ray(&Ray::new(*origin, Vec3::default(), 0.0));
+        self.rotation.rotate(object.random(&local.origin) * self.scale)
+    }
+
+    fn corners(obj_box: &BoundingBox) -> Vec<Vec3> {
+        let mut corners = vec![];
+
+        for i in 0..2 {
+            for j in 0..2 {
+                for k in 0..2 {
+                    corners.push(Vec3::new(
+                        if i == 0 { obj_box.min.x } else { obj_box.max.x },
+                        if j == 0 { obj_box.min.y } else { obj_box.max.y },
+                        if k == 0 { obj_box.min.z } else { obj_box.max.z }
+                    ));
+                }
+            }
+        }
+
+        corners
+    }
+
+    pub fn transform_box(&self, obj_box: &BoundingBox) -> BoundingBox {
+        let points: Vec<Vec3> = Self::corners(obj_box).iter().map(|corner| self.apply(*corner)).collect();
+        BoundingBox::from_points(&points)
+    }
+}
+
+pub struct AnimatedTransform {
+    object: Rc<dyn Hittable>,
+    keyframes: Vec<Keyframe>,
+}
+
+impl AnimatedTransform {
+    pub fn new(object: Rc<dyn Hittable>, mut keyframes: Vec<Keyframe>) -> Self {
+        if keyframes.is_empty() {
+            panic!("An animated transform needs at least one keyframe");
+        }
+
+        keyframes.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+
+        Self {
+            object,
+            keyframes
+        }
+    }
+
+    pub fn interpolate(&self, time: Float) -> Keyframe {
+        let first = self.keyframes[0];
+        let last = self.keyframes[self.keyframes.len() - 1];
+
+        if time <= first.time {
+            return first;
+        }
+        if time >= last.time {
+            return last;
+        }
+
+        let idx = self.keyframes.iter().position(|keyframe| keyframe.time > time).unwrap();
+        let (a, b) = (self.keyframes[idx - 1], self.keyframes[idx]);
+        let t = (time - a.time) / (b.time - a.time);
+
+        Keyframe {
+            time,
+            translation: a.translation + (b.translation - a.translation) * t,
+            rotation: Quaternion::slerp(a.rotation, b.rotation, t),
+            scale: a.scale + (b.scale - a.scale) * t,
+        }
+    }
+}
+
+impl Hittable for AnimatedTransform {
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
+        self.interpolate(ray.time).hit(self.object.as_ref(), ray, tmin, tmax)
+    }
+
+    fn occluded(&self, ray: &Ray, tmin: Float, tmax: Float) -> bool {
+        self.interpolate(ray.time).occluded(self.object.as_ref(), ray, tmin, tmax)
+    }
+
+    //a light sample has no time, both use the first keyframe so the density still matches the samples
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> Float {
+        self.keyframes[0].pdf_value(self.object.as_ref(), origin, direction)
+    }
+
+    fn random(&self, origin: &Vec3) -> Vec3 {
+        self.keyframes[0].random(self.object.as_ref(), origin)
+    }
+
+    //the box is sampled along the shutter interval and padded by how far a corner can move between samples
+    fn bounding_box(&self, t0: Float, t1: Float) -> Option<BoundingBox> {
+        let obj_box = self.object.bounding_box(t0, t1)?;
+        let corners = Keyframe::corners(&obj_box);
+
+        let extent = corners.iter().map(|corner| corner.length()).fold(0.0, max_float);
+
+        let mut times = vec![t0, t1];
+        let mut padding: Float = 0.0;
+
+        for pair in self.keyframes.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            let start = max_float(a.time, t0);
+            let end = min_float(b.time, t1);
+
+            if start >= end {
+                continue;
+            }
+
+            let max_scale = |s: Vec3| max_float(s.x.abs(), max_float(s.y.abs(), s.z.abs()));
+            let travel = (b.translation - a.translation).length()
+                + extent * max_float(max_scale(a.scale), max_scale(b.scale)) * Quaternion::angle_between(a.rotation, b.rotation)
+                + extent * max_scale(b.scale - a.scale);
+            let step = (end - start) / BOX_STEPS as Float;
+
+            padding = max_float(padding, travel / (b.time - a.time) * step / 2.0);
+
+            for idx in 0..=BOX_STEPS {
+                times.push(start + step * idx as Float);
+            }
+        }
+
+        let points: Vec<Vec3> = times.iter()
+            .flat_map(|time| {
+                let frame = self.interpolate(*time);
+                corners.iter().map(move |corner| frame.apply(*corner)).collect::<Vec<Vec3>>()
+            })
+            .collect();
+
+        let result = BoundingBox::from_points(&points);
+        let padding = Vec3::new(padding, padding, padding);
+
+        Some(BoundingBox::new(result.min - padding, result.max + padding))
+    }
+}
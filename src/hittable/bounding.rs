@@ -1,10 +1,12 @@
-use super::hittable::{HitRecord, Hittable};
-use super::{min_f64, max_f64};
+use super::hittable::{HitRecord, Hittable, HittableList};
+use super::{min_float, max_float};
 use super::vec3::Vec3;
 use super::ray::Ray;
+use super::Float;
 
 use std::rc::Rc;
-use std::cmp::Ordering;
+
+use rand::Rng;
 
 #[derive(Default, Copy, Debug)]
 pub struct BoundingBox {
@@ -20,7 +22,11 @@ impl BoundingBox {
         }
     }
 
-    pub fn hit(&self, ray: &Ray, mut tmin: f64, mut tmax: f64) -> bool {
+    pub fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> bool {
+        self.clip(ray, tmin, tmax).is_some()
+    }
+
+    pub fn clip(&self, ray: &Ray, mut tmin: Float, mut tmax: Float) -> Option<(Float, Float)> {
         for idx in 0..3 {
             let inverse_direction = 1.0 / ray.direction[idx];
             
@@ -31,28 +37,54 @@ impl BoundingBox {
                 std::mem::swap(&mut t0, &mut t1);
             }
 
-            tmin = max_f64(tmin, t0);
-            tmax = min_f64(tmax, t1);
+            tmin = max_float(tmin, t0);
+            tmax = min_float(tmax, t1);
 
             if tmax <= tmin {
-                return false;
+                return None;
+            }
+        }
+
+        Some((tmin, tmax))
+    }
+
+    pub fn from_points(points: &[Vec3]) -> Self {
+        let mut min = Vec3::new(Float::MAX, Float::MAX, Float::MAX);
+        let mut max = Vec3::new(Float::MIN, Float::MIN, Float::MIN);
+
+        for point in points.iter() {
+            for idx in 0..3 {
+                min[idx] = min_float(min[idx], point[idx]);
+                max[idx] = max_float(max[idx], point[idx]);
             }
         }
 
-        true
+        Self {
+            min,
+            max
+        }
+    }
+
+    pub fn centroid(&self) -> Vec3 {
+        (self.min + self.max) / 2.0
+    }
+
+    pub fn surface_area(&self) -> Float {
+        let extent = self.max - self.min;
+        2.0 * (extent.x * extent.y + extent.y * extent.z + extent.z * extent.x)
     }
 
     pub fn surrounding_box(a: &Self, b: &Self) -> Self {
         let min = Vec3::new(
-            min_f64(a.min.x, b.min.x),
-            min_f64(a.min.y, b.min.y),
-            min_f64(a.min.z, b.min.z)
+            min_float(a.min.x, b.min.x),
+            min_float(a.min.y, b.min.y),
+            min_float(a.min.z, b.min.z)
         );
 
         let max = Vec3::new(
-            max_f64(a.max.x, b.max.x),
-            max_f64(a.max.y, b.max.y),
-            max_f64(a.max.z, b.max.z)
+            max_float(a.max.x, b.max.x),
+            max_float(a.max.y, b.max.y),
+            max_float(a.max.z, b.max.z)
         );
 
         Self {
@@ -68,116 +100,271 @@ impl Clone for BoundingBox {
     }
 }
 
+const SAH_BINS: usize = 12;
+const MAX_LEAF_SIZE: usize = 4;
+//cost of visiting a node relative to intersecting a primitive
+pub const TRAVERSAL_COST: Float = 0.125;
+
 pub struct BvhNode {
     pub left: Rc<dyn Hittable>,
     pub right: Rc<dyn Hittable>,
-    obj_box: BoundingBox
+    unbounded: Vec<Rc<dyn Hittable>>,
+    obj_box: Option<BoundingBox>,
+    //how many of left and right hold geometry, a light sample picks evenly between them and the unbounded objects
+    children: usize
 }
 
 impl BvhNode {
-    pub fn new(objects: &mut Vec<Rc<dyn Hittable>>, start: usize, end: usize, t0: f64, t1: f64) -> Self {
-        let left;
-        let right;
-        let size = end - start;
-
-        // FIXME: PICK RANDOM AXIS AND SORT ONLY THE SUBARRAY
-        let axis = 0;
-
-        match size {
-            1 => {
-                left = objects[start].clone();
-                right = objects[start].clone();
-            },
-            2 => {
-                if Self::box_compare(&objects[start], &objects[start+1], axis).unwrap() == Ordering::Less {
-                    left = objects[start].clone();
-                    right = objects[start+1].clone();
-                }
-                else{
-                    left = objects[start+1].clone();
-                    right = objects[start].clone(); 
-                }
-            },
-            _ => {
-                objects.sort_by(|a, b| {
-                    Self::box_compare(&a, &b, axis).unwrap()
-                });
-
-                let mid = start + size / 2;
-                left = Rc::new(BvhNode::new(objects, start, mid, t0, t1));
-                right = Rc::new(BvhNode::new(objects, mid, end, t0, t1));
+    pub fn new(objects: &mut [Rc<dyn Hittable>], start: usize, end: usize, t0: Float, t1: Float) -> Self {
+        let mut items = vec![];
+        let mut unbounded = vec![];
+
+        for object in objects[start..end].iter() {
+            match object.bounding_box(t0, t1) {
+                Some(obj_box) => items.push((object.clone(), obj_box)),
+                None => unbounded.push(object.clone())
             }
-        };
+        }
+
+        let mut node = Self::build(&mut items);
 
-        let left_box = if let Some(obj_box) = left.bounding_box(t0, t1) {
-            obj_box
+        //the slice ends up in tree order with the unbounded objects after the bounded ones
+        let bounded = items.len();
+        for (idx, (object, _)) in items.into_iter().enumerate() {
+            objects[start + idx] = object;
         }
-        else {
-            panic!("No box for left object");
-        };
+        for (idx, object) in unbounded.iter().enumerate() {
+            objects[start + bounded + idx] = object.clone();
+        }
+
+        node.unbounded = unbounded;
+        node
+    }
 
-        let right_box = if let Some(obj_box) = right.bounding_box(t0, t1) {
-            obj_box
+    fn build(items: &mut [(Rc<dyn Hittable>, BoundingBox)]) -> Self {
+        let empty: Rc<dyn Hittable> = Rc::new(HittableList::new());
+
+        if items.is_empty() {
+            return Self {
+                left: empty.clone(),
+                right: empty,
+                unbounded: vec![],
+                obj_box: None,
+                children: 0
+            };
         }
-        else {
-            panic!("No box for right object");
-        };
+
+        match sah_partition(items, MAX_LEAF_SIZE) {
+            Some((mid, _)) => Self::split(items, mid),
+            None => Self {
+                left: Self::leaf(items),
+                right: empty,
+                unbounded: vec![],
+                obj_box: Some(Self::enclosing_box(items)),
+                children: 1
+            }
+        }
+    }
+
+    fn split(items: &mut [(Rc<dyn Hittable>, BoundingBox)], mid: usize) -> Self {
+        let obj_box = Self::enclosing_box(items);
+        let (left, right) = items.split_at_mut(mid);
 
         Self {
-            left,
-            right,
-            obj_box: BoundingBox::surrounding_box(&left_box, &right_box)
+            left: Self::subtree(left),
+            right: Self::subtree(right),
+            unbounded: vec![],
+            obj_box: Some(obj_box),
+            children: 2
         }
-    }   
+    }
 
-    fn box_compare(a: &Rc<dyn Hittable>, b: &Rc<dyn Hittable>, axis: usize) -> Option<Ordering> {
-        let box_a = if let Some(obj_box) = a.bounding_box(0.0, 0.0) {
-            obj_box
+    fn subtree(items: &mut [(Rc<dyn Hittable>, BoundingBox)]) -> Rc<dyn Hittable> {
+        match sah_partition(items, MAX_LEAF_SIZE) {
+            Some((mid, _)) => Rc::new(Self::split(items, mid)),
+            None => Self::leaf(items)
         }
-        else {
-            panic!("No box for parameter A");
-        };
+    }
+
+    fn enclosing_box(items: &[(Rc<dyn Hittable>, BoundingBox)]) -> BoundingBox {
+        items[1..].iter().fold(items[0].1, |acc, (_, obj_box)| BoundingBox::surrounding_box(&acc, obj_box))
+    }
 
-        let box_b = if let Some(obj_box) = b.bounding_box(0.0, 0.0) {
-            obj_box
+    fn leaf(items: &[(Rc<dyn Hittable>, BoundingBox)]) -> Rc<dyn Hittable> {
+        if items.len() == 1 {
+            items[0].0.clone()
         }
         else {
-            panic!("No box for parameter B");
-        };
+            Rc::new(HittableList::new_from_vec(items.iter().map(|(object, _)| object.clone()).collect()))
+        }
+    }
+}
+
+//binned surface area heuristic, reorders the items and returns where and on which axis to split them or None when a leaf is cheaper
+pub fn sah_partition<T>(items: &mut [(T, BoundingBox)], max_leaf_size: usize) -> Option<(usize, usize)> {
+    let size = items.len();
+
+    if size <= 1 {
+        return None;
+    }
+
+    let centroids = BoundingBox::from_points(&items.iter().map(|(_, obj_box)| obj_box.centroid()).collect::<Vec<Vec3>>());
+    let parent_area = items[1..].iter()
+        .fold(items[0].1, |acc, (_, obj_box)| BoundingBox::surrounding_box(&acc, obj_box))
+        .surface_area();
+
+    let bin_of = |obj_box: &BoundingBox, axis: usize| -> usize {
+        let extent = centroids.max[axis] - centroids.min[axis];
+        let bin = ((obj_box.centroid()[axis] - centroids.min[axis]) / extent * SAH_BINS as Float) as usize;
+        bin.min(SAH_BINS - 1)
+    };
+
+    let mut best: Option<(Float, usize, usize)> = None;
 
-        box_a.min[axis].partial_cmp(&box_b.min[axis])
+    for axis in 0..3 {
+        if centroids.max[axis] - centroids.min[axis] <= 0.0 {
+            continue;
+        }
+
+        let mut counts = [0usize; SAH_BINS];
+        let mut boxes: [Option<BoundingBox>; SAH_BINS] = [None; SAH_BINS];
+
+        for (_, obj_box) in items.iter() {
+            let bin = bin_of(obj_box, axis);
+            counts[bin] += 1;
+            boxes[bin] = Some(boxes[bin].map_or(*obj_box, |acc| BoundingBox::surrounding_box(&acc, obj_box)));
+        }
+
+        //sweeps from the right to know the cost of every split in one more sweep from the left
+        let mut right_costs = [0.0; SAH_BINS];
+        let mut right_box: Option<BoundingBox> = None;
+        let mut right_count = 0;
+
+        for bin in (1..SAH_BINS).rev() {
+            right_count += counts[bin];
+            right_box = merge(right_box, boxes[bin]);
+            right_costs[bin] = right_box.map_or(0.0, |obj_box| obj_box.surface_area() * right_count as Float);
+        }
+
+        let mut left_box: Option<BoundingBox> = None;
+        let mut left_count = 0;
+
+        for bin in 0..SAH_BINS - 1 {
+            left_count += counts[bin];
+            left_box = merge(left_box, boxes[bin]);
+
+            if left_count == 0 || left_count == size {
+                continue;
+            }
+
+            let left_cost = left_box.map_or(0.0, |obj_box| obj_box.surface_area() * left_count as Float);
+            let cost = TRAVERSAL_COST + (left_cost + right_costs[bin + 1]) / parent_area.max(Float::MIN_POSITIVE);
+
+            if best.is_none_or(|(best_cost, _, _)| cost < best_cost) {
+                best = Some((cost, axis, bin));
+            }
+        }
+    }
+
+    match best {
+        Some((cost, axis, bin)) if cost < size as Float || size > max_leaf_size => {
+            let mut mid = 0;
+
+            for idx in 0..size {
+                if bin_of(&items[idx].1, axis) <= bin {
+                    items.swap(idx, mid);
+                    mid += 1;
+                }
+            }
+
+            Some((mid, axis))
+        },
+        Some(_) => None,
+        //every centroid is in the same place, only splitting in half can keep the leaves small
+        None if size > max_leaf_size => Some((size / 2, 0)),
+        None => None
+    }
+}
+
+fn merge(a: Option<BoundingBox>, b: Option<BoundingBox>) -> Option<BoundingBox> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(BoundingBox::surrounding_box(&a, &b)),
+        (a, None) => a,
+        (None, b) => b
     }
 }
 
 impl Hittable for BvhNode {
-    fn hit(&self, ray: &Ray, tmin: f64, tmax: f64) -> Option<HitRecord> {
-        if !self.obj_box.hit(ray, tmin, tmax) {
-            return None;
+    fn hit(&self, ray: &Ray, tmin: Float, mut tmax: Float) -> Option<HitRecord> {
+        let mut result = None;
+
+        for object in self.unbounded.iter() {
+            if let Some(record) = object.hit(ray, tmin, tmax) {
+                tmax = record.time;
+                result = Some(record);
+            }
+        }
+
+        match self.obj_box {
+            Some(obj_box) if obj_box.hit(ray, tmin, tmax) => (),
+            _ => return result
         }
 
         let hit_left = self.left.hit(ray, tmin, tmax);
         let hit_right = self.right.hit(ray, tmin, tmax);
 
-        if hit_left.is_none() && hit_right.is_none() {
-            None
+        match (hit_left, hit_right) {
+            (Some(left), Some(right)) => Some(if left.time < right.time { left } else { right }),
+            (Some(left), None) => Some(left),
+            (None, right) => right.or(result),
         }
-        else{
-            if hit_left.is_none() {
-                hit_right
-            }
-            else if hit_right.is_none() {
-                hit_left
-            }
-            else{
-                let left = hit_left.unwrap();
-                let right = hit_right.unwrap();
-                
-                Some(if left.time < right.time { left } else { right })
-            }
+    }
+
+    fn occluded(&self, ray: &Ray, tmin: Float, tmax: Float) -> bool {
+        if self.unbounded.iter().any(|object| object.occluded(ray, tmin, tmax)) {
+            return true;
+        }
+
+        match self.obj_box {
+            Some(obj_box) if obj_box.hit(ray, tmin, tmax) => self.left.occluded(ray, tmin, tmax) || self.right.occluded(ray, tmin, tmax),
+            _ => false
+        }
+    }
+
+    fn bounding_box(&self, _t0: Float, _t1: Float) -> Option<BoundingBox> {
+        if self.unbounded.is_empty() { self.obj_box } else { None }
+    }
+
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> Float {
+        let count = self.unbounded.len() + self.children;
+
+        if count == 0 {
+            return 0.0;
         }
+
+        let children = [&self.left, &self.right];
+        let sum: Float = self.unbounded.iter()
+            .chain(children[..self.children].iter().copied())
+            .map(|object| object.pdf_value(origin, direction))
+            .sum();
+
+        sum / count as Float
     }
 
-    fn bounding_box(&self, _t0: f64, _t1: f64) -> Option<BoundingBox> {
-        Some(self.obj_box)
+    fn random(&self, origin: &Vec3) -> Vec3 {
+        let count = self.unbounded.len() + self.children;
+
+        //same fallback as an empty HittableList
+        if count == 0 {
+            return Vec3::new(1.0, 0.0, 0.0);
+        }
+
+        let idx = rand::thread_rng().gen_range(0, count);
+
+        match idx.checked_sub(self.unbounded.len()) {
+            None => self.unbounded[idx].random(origin),
+            Some(0) => self.left.random(origin),
+            Some(_) => self.right.random(origin)
+        }
     }
 }
\ No newline at end of file
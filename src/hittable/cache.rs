@@ -0,0 +1,186 @@
+use super::hittable::Hittable;
+use super::linear::LinearNode;
+use super::Float;
+
+use std::convert::TryInto;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::ops::Deref;
+use std::path::Path;
+use std::rc::Rc;
+
+use memmap2::Mmap;
+
+//header, then the nodes as they are in memory, then the bounded objects in tree order and the unbounded ones as indices into the input
+//the version changes with the layout and with anything that changes the tree the builder makes for the same boxes
+const MAGIC: &[u8; 8] = b"RTLBVH\0\0";
+pub const VERSION: u32 = 1;
+//read back as a different number on a machine with the other byte order
+const BYTE_ORDER: u32 = 0x0102_0304;
+const HEADER_SIZE: usize = 48;
+
+//fnv-1a over the boxes the builder sees, two inputs with the same boxes get the same tree so the shapes themselves don't matter
+//values are fed at their own width, a cache written in the other precision never matches
+pub fn geometry_key(objects: &[Rc<dyn Hittable>], t0: Float, t1: Float) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut feed = |bytes: &[u8]| {
+        for byte in bytes.iter() {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    };
+
+    feed(&t0.to_le_bytes());
+    feed(&t1.to_le_bytes());
+    feed(&(objects.len() as u64).to_le_bytes());
+
+    for object in objects.iter() {
+        match object.bounding_box(t0, t1) {
+            Some(obj_box) => {
+                for idx in 0..3 {
+                    feed(&obj_box.min[idx].to_le_bytes());
+                    feed(&obj_box.max[idx].to_le_bytes());
+                }
+            },
+            //no finite box has these bits
+            None => feed(&u64::MAX.to_le_bytes())
+        }
+    }
+
+    hash
+}
+
+//written next to the target and renamed over it, a tree still mapped from the old file keeps reading the old one
+pub fn write(path: &Path, key: u64, nodes: &[LinearNode], order: &[usize], unbounded: &[usize]) -> io::Result<()> {
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".partial");
+
+    let mut file = BufWriter::new(File::create(&partial)?);
+
+    file.write_all(MAGIC)?;
+    file.write_all(&VERSION.to_ne_bytes())?;
+    file.write_all(&BYTE_ORDER.to_ne_bytes())?;
+    file.write_all(&key.to_ne_bytes())?;
+
+    for size in [nodes.len(), order.len(), unbounded.len()] {
+        file.write_all(&(size as u64).to_ne_bytes())?;
+    }
+
+    //plain old data with a C layout and no padding
+    let bytes = unsafe { std::slice::from_raw_parts(nodes.as_ptr() as *const u8, std::mem::size_of_val(nodes)) };
+    file.write_all(bytes)?;
+
+    for idx in order.iter().chain(unbounded.iter()) {
+        file.write_all(&(*idx as u32).to_ne_bytes())?;
+    }
+
+    file.flush()?;
+    drop(file);
+
+    fs::rename(&partial, path)
+}
+
+//a cache file checked against the key and mapped, the nodes are read in place and only paged in when a ray reaches them
+pub struct CacheFile {
+    map: Mmap,
+    nodes: usize,
+    objects: usize,
+    unbounded: usize
+}
+
+impl CacheFile {
+    pub fn open(path: &Path, key: u64) -> Option<Self> {
+        let file = File::open(path).ok()?;
+        //the file must not be changed by someone else while it is mapped, like any other mapping
+        let map = unsafe { Mmap::map(&file) }.ok()?;
+
+        if map.len() < HEADER_SIZE || &map[..8] != MAGIC {
+            return None;
+        }
+
+        let word = |offset: usize| u32::from_ne_bytes(map[offset..offset + 4].try_into().unwrap());
+        let long = |offset: usize| u64::from_ne_bytes(map[offset..offset + 8].try_into().unwrap());
+
+        if word(8) != VERSION || word(12) != BYTE_ORDER || long(16) != key {
+            return None;
+        }
+
+        let (nodes, objects, unbounded) = (long(24) as usize, long(32) as usize, long(40) as usize);
+        let size = nodes.checked_mul(std::mem::size_of::<LinearNode>())?
+            .checked_add(objects.checked_add(unbounded)?.checked_mul(4)?)?
+            .checked_add(HEADER_SIZE)?;
+
+        if map.len() != size || map[HEADER_SIZE..].as_ptr().align_offset(std::mem::align_of::<LinearNode>()) != 0 {
+            return None;
+        }
+
+        let result = Self {
+            map,
+            nodes,
+            objects,
+            unbounded
+        };
+
+        let inputs = objects + unbounded;
+        if result.order().chain(result.unbounded()).any(|idx| idx >= inputs) {
+            return None;
+        }
+
+        Some(result)
+    }
+
+    pub fn nodes(&self) -> &[LinearNode] {
+        //size and alignment were checked when opening and every bit pattern is a valid node
+        unsafe { std::slice::from_raw_parts(self.map[HEADER_SIZE..].as_ptr() as *const LinearNode, self.nodes) }
+    }
+
+    fn indices(&self, start: usize, count: usize) -> impl Iterator<Item = usize> + '_ {
+        self.map[start..start + 4 * count]
+            .chunks_exact(4)
+            .map(|bytes| u32::from_ne_bytes(bytes.try_into().unwrap()) as usize)
+    }
+
+    pub fn order(&self) -> impl Iterator<Item = usize> + '_ {
+        self.indices(HEADER_SIZE + self.nodes * std::mem::size_of::<LinearNode>(), self.objects)
+    }
+
+    pub fn unbounded(&self) -> impl Iterator<Item = usize> + '_ {
+        self.indices(HEADER_SIZE + self.nodes * std::mem::size_of::<LinearNode>() + 4 * self.objects, self.unbounded)
+    }
+}
+
+//the nodes of a LinearBvh, built in memory or read from a cache file until something changes them
+pub enum NodeStorage {
+    Owned(Vec<LinearNode>),
+    Mapped(CacheFile)
+}
+
+impl NodeStorage {
+    pub fn to_mut(&mut self) -> &mut Vec<LinearNode> {
+        if let NodeStorage::Mapped(file) = self {
+            *self = NodeStorage::Owned(file.nodes().to_vec());
+        }
+
+        match self {
+            NodeStorage::Owned(nodes) => nodes,
+            NodeStorage::Mapped(_) => unreachable!()
+        }
+    }
+}
+
+impl Default for NodeStorage {
+    fn default() -> Self {
+        NodeStorage::Owned(vec![])
+    }
+}
+
+impl Deref for NodeStorage {
+    type Target = [LinearNode];
+
+    fn deref(&self) -> &[LinearNode] {
+        match self {
+            NodeStorage::Owned(nodes) => nodes,
+            NodeStorage::Mapped(file) => file.nodes()
+        }
+    }
+}
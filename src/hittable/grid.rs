@@ -0,0 +1,247 @@
+use super::bounding::BoundingBox;
+use super::hittable::{HitRecord, Hittable};
+use super::vec3::Vec3;
+use super::ray::Ray;
+use super::Float;
+
+use std::rc::Rc;
+
+//cells along the longest axis per cube root of the objects, as in pbrt
+const DENSITY: Float = 3.0;
+const MAX_RESOLUTION: usize = 128;
+//objects spanning several cells are met again in the next ones, the last few tested are remembered and skipped
+const RECENT: usize = 8;
+
+pub struct UniformGrid {
+    objects: Vec<Rc<dyn Hittable>>,
+    unbounded: Vec<Rc<dyn Hittable>>,
+    bounds: BoundingBox,
+    resolution: [usize; 3],
+    cell_size: Vec3,
+    //the objects of cell i are cell_objects[cell_starts[i]..cell_starts[i + 1]]
+    cell_starts: Vec<u32>,
+    cell_objects: Vec<u32>
+}
+
+impl UniformGrid {
+    pub fn new(objects: &[Rc<dyn Hittable>], t0: Float, t1: Float) -> Self {
+        let mut bounded = vec![];
+        let mut boxes = vec![];
+        let mut unbounded = vec![];
+
+        for object in objects.iter() {
+            match object.bounding_box(t0, t1) {
+                Some(obj_box) => {
+                    bounded.push(object.clone());
+                    boxes.push(obj_box);
+                },
+                None => unbounded.push(object.clone())
+            }
+        }
+
+        if boxes.is_empty() {
+            return Self {
+                objects: bounded,
+                unbounded,
+                bounds: BoundingBox::default(),
+                resolution: [0; 3],
+                cell_size: Vec3::default(),
+                cell_starts: vec![0],
+                cell_objects: vec![]
+            };
+        }
+
+        //padded so a flat scene still has some thickness and every box is strictly inside
+        let bounds = boxes[1..].iter().fold(boxes[0], |acc, obj_box| BoundingBox::surrounding_box(&acc, obj_box));
+        let padding = (bounds.max - bounds.min).length() * 1e-6 + 1e-9;
+        let padding = Vec3::new(padding, padding, padding);
+        let bounds = BoundingBox::new(bounds.min - padding, bounds.max + padding);
+
+        let extent = bounds.max - bounds.min;
+        let longest = extent.x.max(extent.y).max(extent.z);
+        let cells = DENSITY * (boxes.len() as Float).cbrt() / longest;
+
+        let mut resolution = [0; 3];
+        for axis in 0..3 {
+            resolution[axis] = ((extent[axis] * cells).round() as usize).clamp(1, MAX_RESOLUTION);
+        }
+
+        let cell_size = Vec3::new(
+            extent.x / resolution[0] as Float,
+            extent.y / resolution[1] as Float,
+            extent.z / resolution[2] as Float
+        );
+
+        let mut grid = Self {
+            objects: bounded,
+            unbounded,
+            bounds,
+            resolution,
+            cell_size,
+            cell_starts: vec![0; resolution[0] * resolution[1] * resolution[2] + 1],
+            cell_objects: vec![]
+        };
+
+        //counts first so every cell gets a contiguous range of one array
+        for obj_box in boxes.iter() {
+            for cell in grid.cells(obj_box) {
+                grid.cell_starts[cell + 1] += 1;
+            }
+        }
+
+        for cell in 1..grid.cell_starts.len() {
+            grid.cell_starts[cell] += grid.cell_starts[cell - 1];
+        }
+
+        let mut next = grid.cell_starts.clone();
+        grid.cell_objects = vec![0; *grid.cell_starts.last().unwrap() as usize];
+
+        for (idx, obj_box) in boxes.iter().enumerate() {
+            for cell in grid.cells(obj_box) {
+                grid.cell_objects[next[cell] as usize] = idx as u32;
+                next[cell] += 1;
+            }
+        }
+
+        grid
+    }
+
+    fn cell_of(&self, point: &Vec3, axis: usize) -> usize {
+        let cell = ((point[axis] - self.bounds.min[axis]) / self.cell_size[axis]).floor();
+        num::clamp(cell, 0.0, (self.resolution[axis] - 1) as Float) as usize
+    }
+
+    fn index(&self, cell: [usize; 3]) -> usize {
+        (cell[2] * self.resolution[1] + cell[1]) * self.resolution[0] + cell[0]
+    }
+
+    fn cells(&self, obj_box: &BoundingBox) -> Vec<usize> {
+        let low: Vec<usize> = (0..3).map(|axis| self.cell_of(&obj_box.min, axis)).collect();
+        let high: Vec<usize> = (0..3).map(|axis| self.cell_of(&obj_box.max, axis)).collect();
+        let mut result = vec![];
+
+        for z in low[2]..=high[2] {
+            for y in low[1]..=high[1] {
+                for x in low[0]..=high[0] {
+                    result.push(self.index([x, y, z]));
+                }
+            }
+        }
+
+        result
+    }
+
+    pub fn resolution(&self) -> [usize; 3] {
+        self.resolution
+    }
+
+    //3d-dda, calls visit on the objects of every cell the ray crosses in order until it returns true
+    //visit gets the time the ray leaves the cell, a hit before it can't be beaten by anything further along
+    fn traverse(&self, ray: &Ray, tmin: Float, tmax: Float, mut visit: impl FnMut(&[u32], Float) -> bool) {
+        let (enter, exit) = match self.bounds.clip(ray, tmin, tmax) {
+            Some(interval) if !self.objects.is_empty() => interval,
+            _ => return
+        };
+
+        let point = ray.at(enter);
+        let mut cell = [0; 3];
+        let mut next = [Float::INFINITY; 3];
+        let mut delta = [Float::INFINITY; 3];
+        let mut step = [0isize; 3];
+
+        for axis in 0..3 {
+            cell[axis] = self.cell_of(&point, axis);
+
+            if ray.direction[axis] > 0.0 {
+                let boundary = self.bounds.min[axis] + (cell[axis] + 1) as Float * self.cell_size[axis];
+                next[axis] = enter + (boundary - point[axis]) / ray.direction[axis];
+                delta[axis] = self.cell_size[axis] / ray.direction[axis];
+                step[axis] = 1;
+            }
+            else if ray.direction[axis] < 0.0 {
+                let boundary = self.bounds.min[axis] + cell[axis] as Float * self.cell_size[axis];
+                next[axis] = enter + (boundary - point[axis]) / ray.direction[axis];
+                delta[axis] = -self.cell_size[axis] / ray.direction[axis];
+                step[axis] = -1;
+            }
+        }
+
+        loop {
+            let idx = self.index(cell);
+            let start = self.cell_starts[idx] as usize;
+            let end = self.cell_starts[idx + 1] as usize;
+
+            let axis = if next[0] < next[1] && next[0] < next[2] { 0 } else if next[1] < next[2] { 1 } else { 2 };
+
+            if visit(&self.cell_objects[start..end], next[axis]) || next[axis] > exit {
+                return;
+            }
+
+            let moved = cell[axis] as isize + step[axis];
+
+            if moved < 0 || moved >= self.resolution[axis] as isize {
+                return;
+            }
+
+            cell[axis] = moved as usize;
+            next[axis] += delta[axis];
+        }
+    }
+}
+
+impl Hittable for UniformGrid {
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
+        let mut result: Option<HitRecord> = None;
+        let mut closest = tmax;
+
+        for object in self.unbounded.iter() {
+            if let Some(record) = object.hit(ray, tmin, closest) {
+                closest = record.time;
+                result = Some(record);
+            }
+        }
+
+        let mut recent = [u32::MAX; RECENT];
+        let mut cursor = 0;
+
+        self.traverse(ray, tmin, closest, |objects, leave| {
+            for idx in objects.iter() {
+                //a miss stays a miss now that the interval only shrinks, a hit is already the closest one
+                if recent.contains(idx) {
+                    continue;
+                }
+
+                recent[cursor] = *idx;
+                cursor = (cursor + 1) % RECENT;
+
+                if let Some(record) = self.objects[*idx as usize].hit(ray, tmin, closest) {
+                    closest = record.time;
+                    result = Some(record);
+                }
+            }
+
+            closest <= leave
+        });
+
+        result
+    }
+
+    fn occluded(&self, ray: &Ray, tmin: Float, tmax: Float) -> bool {
+        if self.unbounded.iter().any(|object| object.occluded(ray, tmin, tmax)) {
+            return true;
+        }
+
+        let mut occluded = false;
+
+        self.traverse(ray, tmin, tmax, |objects, _| {
+            occluded = objects.iter().any(|idx| self.objects[*idx as usize].occluded(ray, tmin, tmax));
+            occluded
+        });
+
+        occluded
+    }
+
+    fn bounding_box(&self, _t0: Float, _t1: Float) -> Option<BoundingBox> {
+        if self.unbounded.is_empty() && !self.objects.is_empty() { Some(self.bounds) } else { None }
+    }
+}
@@ -1,47 +1,92 @@
 use super::material::material::Material;
+use super::color::Color;
 use super::bounding::BoundingBox;
-use super::{min_f64, max_f64};
+use super::{min_float, max_float, gamma};
 use super::vec3::Vec3;
 use super::ray::Ray;
+use super::Float;
 
 use std::rc::Rc;
-use std::f64;
 
+use rand::Rng;
+
+#[derive(Clone)]
 pub struct HitRecord {
     pub point: Vec3,
     pub normal: Vec3,
-    pub time: f64,
-    pub u: f64,
-    pub v: f64,
+    pub geometric_normal: Vec3,
+    pub time: Float,
+    pub u: Float,
+    pub v: Float,
     pub front_face: bool,
+    pub dpdu: Vec3,
+    pub dpdv: Vec3,
+    //per primitive color, like a point cloud's, diffuse materials multiply their albedo by it
+    pub color: Option<Color>,
     pub material: Rc<dyn Material>
 }
 
 impl HitRecord {
-    pub fn new(ray: &Ray, point: Vec3, mut normal: Vec3, time: f64, u: f64, v: f64, material: Rc<dyn Material>) -> Self {
+    pub fn new(ray: &Ray, point: Vec3, mut normal: Vec3, time: Float, u: Float, v: Float, material: Rc<dyn Material>) -> Self {
         let front_face = Vec3::dot_product(ray.direction, normal) < 0.0;
         normal = if front_face { normal } else { -normal };
         Self {
             point,
             normal,
+            geometric_normal: normal,
             time,
             u,
             v,
             front_face,
+            dpdu: Vec3::default(),
+            dpdv: Vec3::default(),
+            color: None,
             material
         }
     }
 }
 
 pub trait Hittable {
-    fn hit(&self, ray: &Ray, tmin: f64, tmax: f64) -> Option<HitRecord>;
-    fn bounding_box(&self, t0: f64, t1: f64) -> Option<BoundingBox>;
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord>;
+    fn bounding_box(&self, t0: Float, t1: Float) -> Option<BoundingBox>;
+
+    //any hit in the interval, shadow rays don't need the closest one or its record
+    fn occluded(&self, ray: &Ray, tmin: Float, tmax: Float) -> bool {
+        self.hit(ray, tmin, tmax).is_some()
+    }
+
+    //every surface crossing sorted by time, front_face tells if the ray is entering
+    fn hit_all(&self, ray: &Ray, mut tmin: Float, tmax: Float) -> Vec<HitRecord> {
+        let mut result = vec![];
+
+        while let Some(record) = self.hit(ray, tmin, tmax) {
+            //relative past large times, where adding a fixed step would not change the value
+            tmin = record.time + max_float(0.0001, record.time.abs() * gamma(3));
+            result.push(record);
+        }
+
+        result
+    }
+
+    fn pdf_value(&self, _origin: &Vec3, _direction: &Vec3) -> Float {
+        0.0
+    }
+
+    fn random(&self, _origin: &Vec3) -> Vec3 {
+        Vec3::new(1.0, 0.0, 0.0)
+    }
 }
 
 pub struct HittableList {
     objects: Vec<Rc<dyn Hittable>>
 }
 
+impl Default for HittableList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl HittableList {
     pub fn new() -> Self {
         Self {
@@ -61,7 +106,7 @@ impl HittableList {
 }
 
 impl Hittable for HittableList {
-    fn hit(&self, ray: &Ray, tmin: f64, mut tmax: f64) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, tmin: Float, mut tmax: Float) -> Option<HitRecord> {
         let mut result = None;
 
         for object in self.objects.iter() {
@@ -74,12 +119,16 @@ impl Hittable for HittableList {
         result
     }
 
-    fn bounding_box(&self, t0: f64, t1: f64) -> Option<BoundingBox> {
+    fn occluded(&self, ray: &Ray, tmin: Float, tmax: Float) -> bool {
+        self.objects.iter().any(|object| object.occluded(ray, tmin, tmax))
+    }
+
+    fn bounding_box(&self, t0: Float, t1: Float) -> Option<BoundingBox> {
         if self.objects.is_empty() {
             return None;
         }
 
-        let mut first = false;
+        let mut first = true;
         let mut result = BoundingBox::default();
 
         for object in self.objects.iter() {
@@ -94,6 +143,30 @@ impl Hittable for HittableList {
 
         Some(result)
     }
+
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> Float {
+        if self.objects.is_empty() {
+            return 0.0;
+        }
+
+        let weight = 1.0 / self.objects.len() as Float;
+
+        self.objects.iter()
+            .map(|object| weight * object.pdf_value(origin, direction))
+            .sum()
+    }
+
+    fn random(&self, origin: &Vec3) -> Vec3 {
+        //same fallback as the default, pdf_value is 0 for an empty list anyway
+        if self.objects.is_empty() {
+            return Vec3::new(1.0, 0.0, 0.0);
+        }
+
+        let mut rng = rand::thread_rng();
+        let idx = rng.gen_range(0, self.objects.len());
+
+        self.objects[idx].random(origin)
+    }
 }
 
 pub struct FlipFace {
@@ -109,7 +182,7 @@ impl FlipFace {
 }
 
 impl Hittable for FlipFace {
-    fn hit(&self, ray: &Ray, tmin: f64, tmax: f64) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
         if let Some(mut record) = self.object.hit(ray, tmin, tmax){
             record.front_face = !record.front_face;
             Some(record)
@@ -119,9 +192,21 @@ impl Hittable for FlipFace {
         }
     }
 
-    fn bounding_box(&self, t0: f64, t1: f64) -> Option<BoundingBox> {
+    fn occluded(&self, ray: &Ray, tmin: Float, tmax: Float) -> bool {
+        self.object.occluded(ray, tmin, tmax)
+    }
+
+    fn bounding_box(&self, t0: Float, t1: Float) -> Option<BoundingBox> {
         self.object.bounding_box(t0, t1)
     }
+
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> Float {
+        self.object.pdf_value(origin, direction)
+    }
+
+    fn random(&self, origin: &Vec3) -> Vec3 {
+        self.object.random(origin)
+    }
 }
 
 pub struct Translate {
@@ -139,64 +224,68 @@ impl Translate {
 }
 
 impl Hittable for Translate {
-    fn hit(&self, ray: &Ray, tmin: f64, tmax: f64) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
         let translated_ray = Ray {
             origin: ray.origin - self.offset,
             ..*ray
         };
 
-        if let Some(record) = self.object.hit(&translated_ray, tmin, tmax) {
-            Some(HitRecord::new(
-                &translated_ray,
-                record.point + self.offset,
-                record.normal,
-                record.time,
-                record.u,
-                record.v,
-                record.material
-            ))            
-        }
-        else{
-            None
-        }
+        self.object.hit(&translated_ray, tmin, tmax).map(|mut record| {
+            record.point = record.point + self.offset;
+            record
+        })
+    }
+
+    fn occluded(&self, ray: &Ray, tmin: Float, tmax: Float) -> bool {
+        let translated_ray = Ray {
+            origin: ray.origin - self.offset,
+            ..*ray
+        };
+
+        self.object.occluded(&translated_ray, tmin, tmax)
     }
 
-    fn bounding_box(&self, t0: f64, t1: f64) -> Option<BoundingBox> {
-        if let Some(obj_box) = self.object.bounding_box(t0, t1) {
-            Some(BoundingBox::new(
+    fn bounding_box(&self, t0: Float, t1: Float) -> Option<BoundingBox> {
+        self.object.bounding_box(t0, t1).map(|obj_box| {
+            BoundingBox::new(
                 obj_box.min + self.offset,
                 obj_box.max + self.offset
-            ))
-        }
-        else {
-            None
-        }
+            )
+        })
+    }
+
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> Float {
+        self.object.pdf_value(&(*origin - self.offset), direction)
+    }
+
+    fn random(&self, origin: &Vec3) -> Vec3 {
+        self.object.random(&(*origin - self.offset))
     }
 }
 
 pub struct RotateY {
-    sin: f64,
-    cos: f64,
+    sin: Float,
+    cos: Float,
     object: Rc<dyn Hittable>,
     obj_box: BoundingBox
 }
 
 impl RotateY {
-    pub fn new(object: Rc<dyn Hittable>, angle: f64) -> Self {
+    pub fn new(object: Rc<dyn Hittable>, angle: Float) -> Self {
         let radians = angle.to_radians();
         let sin = radians.sin();
         let cos = radians.cos();
         let obj_box = object.bounding_box(0.0, 1.0).unwrap();
 
-        let mut min = Vec3::new(f64::MAX, f64::MAX, f64::MAX);
-        let mut max = Vec3::new(f64::MIN, f64::MIN, f64::MIN);
+        let mut min = Vec3::new(Float::MAX, Float::MAX, Float::MAX);
+        let mut max = Vec3::new(Float::MIN, Float::MIN, Float::MIN);
     
         for i in 0..2 {
             for j in 0..2 {
                 for k in 0..2 {
-                    let x = i as f64 * obj_box.max.x + (1.0 - i as f64) * obj_box.min.x;
-                    let y = j as f64 * obj_box.max.y + (1.0 - j as f64) * obj_box.min.y;
-                    let z = k as f64 * obj_box.max.z + (1.0 - k as f64) * obj_box.min.z;
+                    let x = i as Float * obj_box.max.x + (1.0 - i as Float) * obj_box.min.x;
+                    let y = j as Float * obj_box.max.y + (1.0 - j as Float) * obj_box.min.y;
+                    let z = k as Float * obj_box.max.z + (1.0 - k as Float) * obj_box.min.z;
                 
                     let newx = cos * x + sin * z;
                     let newz = -sin * x + cos * z;
@@ -204,8 +293,8 @@ impl RotateY {
                     let tmp = Vec3::new(newx, y, newz);
 
                     for idx in 0..3 {
-                        min[idx] = min_f64(min[idx], tmp[idx]);
-                        max[idx] = max_f64(max[idx], tmp[idx]);
+                        min[idx] = min_float(min[idx], tmp[idx]);
+                        max[idx] = max_float(max[idx], tmp[idx]);
                     }
                 }
             }
@@ -218,47 +307,44 @@ impl RotateY {
             obj_box: BoundingBox::new(min, max),
         }
     }
+
+    fn to_object(&self, v: Vec3) -> Vec3 {
+        Vec3::new(self.cos * v.x - self.sin * v.z, v.y, self.sin * v.x + self.cos * v.z)
+    }
+
+    fn to_world(&self, v: Vec3) -> Vec3 {
+        Vec3::new(self.cos * v.x + self.sin * v.z, v.y, -self.sin * v.x + self.cos * v.z)
+    }
 }
 
 impl Hittable for RotateY {
-    fn hit(&self, ray: &Ray, tmin: f64, tmax: f64) -> Option<HitRecord> {
-        let mut origin = ray.origin;
-        let mut direction = ray.direction;
-    
-        origin.x = self.cos * origin.x - self.sin * origin.z;
-        origin.z = self.sin * origin.x + self.cos * origin.z;
-    
-        direction.x = self.cos * direction.x - self.sin * direction.z;
-        direction.z = self.sin * direction.x + self.cos * direction.z;
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
+        let rotated_ray = Ray::new(self.to_object(ray.origin), self.to_object(ray.direction), ray.time);
+
+        self.object.hit(&rotated_ray, tmin, tmax).map(|mut record| {
+            record.point = self.to_world(record.point);
+            record.normal = self.to_world(record.normal);
+            record.geometric_normal = self.to_world(record.geometric_normal);
+            record.dpdu = self.to_world(record.dpdu);
+            record.dpdv = self.to_world(record.dpdv);
+            record
+        })
+    }
 
-        let rotated_ray = Ray::new(origin, direction, ray.time);
+    fn occluded(&self, ray: &Ray, tmin: Float, tmax: Float) -> bool {
+        let rotated_ray = Ray::new(self.to_object(ray.origin), self.to_object(ray.direction), ray.time);
+        self.object.occluded(&rotated_ray, tmin, tmax)
+    }
 
-        if let Some(record) = self.object.hit(&rotated_ray, tmin, tmax) {
-            let mut point = record.point;
-            let mut normal = record.normal;
+    fn bounding_box(&self, _t0: Float, _t1: Float) -> Option<BoundingBox> {
+        Some(self.obj_box)
+    }
 
-            point.x = self.cos * point.x + self.sin * point.z;
-            point.z = self.cos * point.z - self.sin * point.x;
-        
-            normal.x = self.cos * normal.x + self.sin * normal.z;
-            normal.z = self.cos * normal.z - self.sin * normal.x;
-
-            Some(HitRecord::new(
-                &rotated_ray,
-                point, 
-                normal,
-                record.time,
-                record.u,
-                record.v,
-                record.material.clone()
-            ))
-        }
-        else{
-            None
-        }
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> Float {
+        self.object.pdf_value(&self.to_object(*origin), &self.to_object(*direction))
     }
 
-    fn bounding_box(&self, _t0: f64, _t1: f64) -> Option<BoundingBox> {
-        Some(self.obj_box)
+    fn random(&self, origin: &Vec3) -> Vec3 {
+        self.to_world(self.object.random(&self.to_object(*origin)))
     }
 }
\ No newline at end of file
@@ -0,0 +1,51 @@
+use super::hittable::{HitRecord, Hittable};
+use super::quaternion::Quaternion;
+use super::bounding::BoundingBox;
+use super::animated::Keyframe;
+use super::vec3::Vec3;
+use super::ray::Ray;
+use super::Float;
+
+use std::rc::Rc;
+
+//a placement of shared geometry, a bvh over instances only stores the transforms next to the one shared bottom level
+//Rc like every other wrapper, the geometry and materials below are Rc too so an Arc would not make it shareable across threads
+pub struct Instance {
+    object: Rc<dyn Hittable>,
+    transform: Keyframe
+}
+
+impl Instance {
+    pub fn new(object: Rc<dyn Hittable>, translation: Vec3, rotation: Quaternion, scale: Vec3) -> Self {
+        Self {
+            object,
+            transform: Keyframe::new(0.0, translation, rotation, scale)
+        }
+    }
+
+    pub fn object(&self) -> &Rc<dyn Hittable> {
+        &self.object
+    }
+}
+
+impl Hittable for Instance {
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
+        self.transform.hit(self.object.as_ref(), ray, tmin, tmax)
+    }
+
+    fn occluded(&self, ray: &Ray, tmin: Float, tmax: Float) -> bool {
+        self.transform.occluded(self.object.as_ref(), ray, tmin, tmax)
+    }
+
+    fn bounding_box(&self, t0: Float, t1: Float) -> Option<BoundingBox> {
+        self.object.bounding_box(t0, t1).map(|obj_box| self.transform.transform_box(&obj_box))
+    }
+
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> Float {
+        self.transform.pdf_value(self.object.as_ref(), origin, direction)
+    }
+
+    fn random(&self, origin: &Vec3) -> Vec3 {
+        self.transform.random(self.object.as_ref(), origin)
+    }
+}
@@ -0,0 +1,283 @@
+use super::bounding::BoundingBox;
+use super::hittable::{HitRecord, Hittable};
+use super::vec3::Vec3;
+use super::ray::Ray;
+use super::Float;
+
+use std::rc::Rc;
+
+//costs and limits from pbrt, an intersection is much more expensive than a step down the tree
+const INTERSECTION_COST: Float = 80.0;
+const TRAVERSAL_COST: Float = 1.0;
+const EMPTY_BONUS: Float = 0.5;
+const MAX_LEAF_SIZE: usize = 1;
+const MAX_BAD_REFINES: usize = 3;
+const STACK_SIZE: usize = 64;
+const LEAF: u8 = 3;
+
+//the below child is right after its parent and the above one at offset, leaves own indices[offset..offset + count]
+#[derive(Clone, Copy)]
+struct KdNode {
+    split: Float,
+    offset: u32,
+    count: u32,
+    axis: u8
+}
+
+#[derive(Clone, Copy)]
+struct Edge {
+    time: Float,
+    object: u32,
+    start: bool
+}
+
+pub struct KdTree {
+    objects: Vec<Rc<dyn Hittable>>,
+    unbounded: Vec<Rc<dyn Hittable>>,
+    //an object straddling a split is referenced from both sides
+    indices: Vec<u32>,
+    nodes: Vec<KdNode>,
+    bounds: BoundingBox
+}
+
+impl KdTree {
+    pub fn new(objects: &[Rc<dyn Hittable>], t0: Float, t1: Float) -> Self {
+        let mut bounded = vec![];
+        let mut boxes = vec![];
+        let mut unbounded = vec![];
+
+        for object in objects.iter() {
+            match object.bounding_box(t0, t1) {
+                Some(obj_box) => {
+                    bounded.push(object.clone());
+                    boxes.push(obj_box);
+                },
+                None => unbounded.push(object.clone())
+            }
+        }
+
+        let mut tree = Self {
+            objects: bounded,
+            unbounded,
+            indices: vec![],
+            nodes: vec![],
+            bounds: BoundingBox::default()
+        };
+
+        if boxes.is_empty() {
+            return tree;
+        }
+
+        //padded so a flat scene still has some area to split
+        let bounds = boxes[1..].iter().fold(boxes[0], |acc, obj_box| BoundingBox::surrounding_box(&acc, obj_box));
+        let padding = (bounds.max - bounds.min).length() * 1e-6 + 1e-9;
+        let padding = Vec3::new(padding, padding, padding);
+        tree.bounds = BoundingBox::new(bounds.min - padding, bounds.max + padding);
+
+        let max_depth = (8.0 + 1.3 * (boxes.len() as Float).log2()).round() as usize;
+        let all = (0..boxes.len() as u32).collect();
+        tree.build(&boxes, tree.bounds, all, max_depth, 0);
+        tree
+    }
+
+    fn leaf(&mut self, objects: Vec<u32>) {
+        self.nodes.push(KdNode { split: 0.0, offset: self.indices.len() as u32, count: objects.len() as u32, axis: LEAF });
+        self.indices.extend(objects);
+    }
+
+    fn build(&mut self, boxes: &[BoundingBox], bounds: BoundingBox, objects: Vec<u32>, depth: usize, mut bad_refines: usize) {
+        if objects.len() <= MAX_LEAF_SIZE || depth == 0 {
+            return self.leaf(objects);
+        }
+
+        let extent = bounds.max - bounds.min;
+        let leaf_cost = INTERSECTION_COST * objects.len() as Float;
+        let longest = if extent.x > extent.y && extent.x > extent.z { 0 } else if extent.y > extent.z { 1 } else { 2 };
+
+        //the longest axis first, the others only when it has no split inside the node
+        let mut best: Option<(Float, usize, Vec<Edge>, usize)> = None;
+
+        for retry in 0..3 {
+            let axis = (longest + retry) % 3;
+            let edges = Self::edges(boxes, &objects, axis);
+
+            if let Some((cost, offset)) = Self::best_split(&edges, &bounds, axis, objects.len()) {
+                best = Some((cost, axis, edges, offset));
+                break;
+            }
+        }
+
+        let (cost, axis, edges, offset) = match best {
+            Some(best) => best,
+            None => return self.leaf(objects)
+        };
+
+        if cost > leaf_cost {
+            bad_refines += 1;
+        }
+
+        if (cost > 4.0 * leaf_cost && objects.len() < 16) || bad_refines >= MAX_BAD_REFINES {
+            return self.leaf(objects);
+        }
+
+        let below: Vec<u32> = edges[..offset].iter().filter(|edge| edge.start).map(|edge| edge.object).collect();
+        let above: Vec<u32> = edges[offset + 1..].iter().filter(|edge| !edge.start).map(|edge| edge.object).collect();
+        let split = edges[offset].time;
+
+        let (mut below_bounds, mut above_bounds) = (bounds, bounds);
+        below_bounds.max[axis] = split;
+        above_bounds.min[axis] = split;
+
+        let idx = self.nodes.len();
+        self.nodes.push(KdNode { split, offset: 0, count: 0, axis: axis as u8 });
+
+        self.build(boxes, below_bounds, below, depth - 1, bad_refines);
+        self.nodes[idx].offset = self.nodes.len() as u32;
+        self.build(boxes, above_bounds, above, depth - 1, bad_refines);
+    }
+
+    //starts sort before ends at the same place, so a flat object lying on the split still ends up on one of the sides
+    fn edges(boxes: &[BoundingBox], objects: &[u32], axis: usize) -> Vec<Edge> {
+        let mut edges = Vec::with_capacity(2 * objects.len());
+
+        for object in objects.iter() {
+            let obj_box = &boxes[*object as usize];
+            edges.push(Edge { time: obj_box.min[axis], object: *object, start: true });
+            edges.push(Edge { time: obj_box.max[axis], object: *object, start: false });
+        }
+
+        edges.sort_by(|a, b| a.time.total_cmp(&b.time).then(b.start.cmp(&a.start)));
+        edges
+    }
+
+    //surface area heuristic over every edge strictly inside the node, the cost and the index of the best edge
+    fn best_split(edges: &[Edge], bounds: &BoundingBox, axis: usize, count: usize) -> Option<(Float, usize)> {
+        let extent = bounds.max - bounds.min;
+        let (first, second) = ((axis + 1) % 3, (axis + 2) % 3);
+        let inverse_area = 1.0 / bounds.surface_area();
+
+        let mut best: Option<(Float, usize)> = None;
+        let (mut below, mut above) = (0, count);
+
+        for (idx, edge) in edges.iter().enumerate() {
+            if !edge.start {
+                above -= 1;
+            }
+
+            if edge.time > bounds.min[axis] && edge.time < bounds.max[axis] {
+                let side = extent[first] * extent[second];
+                let perimeter = extent[first] + extent[second];
+                let below_area = 2.0 * (side + (edge.time - bounds.min[axis]) * perimeter);
+                let above_area = 2.0 * (side + (bounds.max[axis] - edge.time) * perimeter);
+
+                let bonus = if below == 0 || above == 0 { EMPTY_BONUS } else { 0.0 };
+                let cost = TRAVERSAL_COST + INTERSECTION_COST * (1.0 - bonus) *
+                    (below_area * inverse_area * below as Float + above_area * inverse_area * above as Float);
+
+                if best.is_none_or(|(best_cost, _)| cost < best_cost) {
+                    best = Some((cost, idx));
+                }
+            }
+
+            if edge.start {
+                below += 1;
+            }
+        }
+
+        best
+    }
+
+    //front to back through the leaves, visit gets the leaf objects and the time the ray leaves the leaf and returns true to stop
+    fn traverse(&self, ray: &Ray, tmin: Float, tmax: Float, mut visit: impl FnMut(&[u32], Float) -> bool) {
+        let (mut near, mut far) = match self.bounds.clip(ray, tmin, tmax) {
+            Some(interval) if !self.nodes.is_empty() => interval,
+            _ => return
+        };
+
+        let mut stack = [(0usize, 0.0, 0.0); STACK_SIZE];
+        let mut size = 0;
+        let mut current = 0;
+
+        loop {
+            let node = &self.nodes[current];
+
+            if node.axis == LEAF {
+                let start = node.offset as usize;
+
+                if visit(&self.indices[start..start + node.count as usize], far) || size == 0 {
+                    return;
+                }
+
+                size -= 1;
+                (current, near, far) = stack[size];
+                continue;
+            }
+
+            let axis = node.axis as usize;
+            let plane = (node.split - ray.origin[axis]) / ray.direction[axis];
+
+            let below_first = ray.origin[axis] < node.split || (ray.origin[axis] == node.split && ray.direction[axis] <= 0.0);
+            let (first, second) = if below_first { (current + 1, node.offset as usize) } else { (node.offset as usize, current + 1) };
+
+            //behind the origin or past the interval only the near side is crossed, before the interval only the far one
+            if plane > far || plane <= 0.0 {
+                current = first;
+            }
+            else if plane < near {
+                current = second;
+            }
+            else {
+                stack[size] = (second, plane, far);
+                size += 1;
+                current = first;
+                far = plane;
+            }
+        }
+    }
+}
+
+impl Hittable for KdTree {
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
+        let mut result: Option<HitRecord> = None;
+        let mut closest = tmax;
+
+        for object in self.unbounded.iter() {
+            if let Some(record) = object.hit(ray, tmin, closest) {
+                closest = record.time;
+                result = Some(record);
+            }
+        }
+
+        self.traverse(ray, tmin, closest, |objects, leave| {
+            for idx in objects.iter() {
+                if let Some(record) = self.objects[*idx as usize].hit(ray, tmin, closest) {
+                    closest = record.time;
+                    result = Some(record);
+                }
+            }
+
+            closest <= leave
+        });
+
+        result
+    }
+
+    fn occluded(&self, ray: &Ray, tmin: Float, tmax: Float) -> bool {
+        if self.unbounded.iter().any(|object| object.occluded(ray, tmin, tmax)) {
+            return true;
+        }
+
+        let mut occluded = false;
+
+        self.traverse(ray, tmin, tmax, |objects, _| {
+            occluded = objects.iter().any(|idx| self.objects[*idx as usize].occluded(ray, tmin, tmax));
+            occluded
+        });
+
+        occluded
+    }
+
+    fn bounding_box(&self, _t0: Float, _t1: Float) -> Option<BoundingBox> {
+        if self.unbounded.is_empty() && !self.nodes.is_empty() { Some(self.bounds) } else { None }
+    }
+}
@@ -0,0 +1,563 @@
+use super::bounding::{BoundingBox, sah_partition, TRAVERSAL_COST};
+use super::hittable::{HitRecord, Hittable};
+use super::cache::{self, geometry_key, CacheFile, NodeStorage};
+use super::{f32_above, f32_below};
+use super::vec3::Vec3;
+use super::ray::Ray;
+use super::Float;
+
+use std::time::{Duration, Instant};
+use std::collections::HashMap;
+use std::path::Path;
+use std::rc::Rc;
+use std::io;
+use std::thread;
+
+const MAX_LEAF_SIZE: usize = 4;
+const STACK_SIZE: usize = 128;
+//past this depth nodes are split in half, so the traversal stack can never overflow
+const MAX_SAH_DEPTH: usize = 64;
+//a subtree is rebuilt once its box grew this much since it was built
+const SUBTREE_GROWTH: Float = 2.0;
+//the whole tree is rebuilt once its cost grew this much since the last full build
+const COST_GROWTH: Float = 1.5;
+//smaller subtrees are not worth a thread
+const PARALLEL_THRESHOLD: usize = 16 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BvhUpdate {
+    Refitted,
+    PartiallyRebuilt(usize),
+    Rebuilt
+}
+
+//32 bytes, leaves own objects[offset..offset + count], inner nodes have count 0, the left child right after them and the right one at offset
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct LinearNode {
+    min: [f32; 3],
+    max: [f32; 3],
+    offset: u32,
+    count: u16,
+    axis: u16
+}
+
+impl LinearNode {
+    fn new(obj_box: &BoundingBox, offset: usize, count: usize, axis: usize) -> Self {
+        Self {
+            min: [f32_below(obj_box.min.x), f32_below(obj_box.min.y), f32_below(obj_box.min.z)],
+            max: [f32_above(obj_box.max.x), f32_above(obj_box.max.y), f32_above(obj_box.max.z)],
+            offset: offset as u32,
+            count: count as u16,
+            axis: axis as u16
+        }
+    }
+
+    pub fn bounding_box(&self) -> BoundingBox {
+        BoundingBox::new(
+            Vec3::new(self.min[0] as Float, self.min[1] as Float, self.min[2] as Float),
+            Vec3::new(self.max[0] as Float, self.max[1] as Float, self.max[2] as Float)
+        )
+    }
+
+    fn hit(&self, origin: &Vec3, inverse_direction: &Vec3, mut tmin: Float, mut tmax: Float) -> bool {
+        for idx in 0..3 {
+            let mut t0 = (self.min[idx] as Float - origin[idx]) * inverse_direction[idx];
+            let mut t1 = (self.max[idx] as Float - origin[idx]) * inverse_direction[idx];
+
+            if inverse_direction[idx] < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+
+            tmin = if t0 > tmin { t0 } else { tmin };
+            tmax = if t1 < tmax { t1 } else { tmax };
+
+            if tmax < tmin {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BuildStats {
+    pub time: Duration,
+    pub threads: usize,
+    pub nodes: usize,
+    pub leaves: usize,
+    pub max_depth: usize,
+    pub average_leaf_size: Float,
+    pub cost: Float
+}
+
+pub struct LinearBvh {
+    objects: Vec<Rc<dyn Hittable>>,
+    nodes: NodeStorage,
+    unbounded: Vec<Rc<dyn Hittable>>,
+    built_areas: Vec<Float>,
+    built_cost: Float,
+    build_time: Duration,
+    threads: usize
+}
+
+impl LinearBvh {
+    pub fn new(objects: &[Rc<dyn Hittable>], t0: Float, t1: Float) -> Self {
+        Self::with_threads(objects, t0, t1, thread::available_parallelism().map_or(1, |threads| threads.get()))
+    }
+
+    //objects are not Send, so the threads only see their indices and boxes and the objects are put in order afterwards
+    pub fn with_threads(objects: &[Rc<dyn Hittable>], t0: Float, t1: Float, threads: usize) -> Self {
+        let start = Instant::now();
+        let mut items = vec![];
+        let mut unbounded = vec![];
+
+        for (idx, object) in objects.iter().enumerate() {
+            match object.bounding_box(t0, t1) {
+                Some(obj_box) => items.push((idx, obj_box)),
+                None => unbounded.push(idx)
+            }
+        }
+
+        let nodes = if items.is_empty() { vec![] } else { Self::build_parallel(&mut items, 0, 0, threads.max(1)) };
+        let order = items.iter().map(|(idx, _)| *idx);
+
+        Self::assemble(objects, NodeStorage::Owned(nodes), order, unbounded.into_iter(), threads.max(1), start)
+    }
+
+    fn assemble(
+        objects: &[Rc<dyn Hittable>],
+        nodes: NodeStorage,
+        order: impl Iterator<Item = usize>,
+        unbounded: impl Iterator<Item = usize>,
+        threads: usize,
+        start: Instant
+    ) -> Self {
+        let mut result = Self {
+            objects: order.map(|idx| objects[idx].clone()).collect(),
+            nodes,
+            unbounded: unbounded.map(|idx| objects[idx].clone()).collect(),
+            built_areas: vec![],
+            built_cost: 0.0,
+            build_time: Duration::default(),
+            threads
+        };
+
+        result.built_areas = result.nodes.iter().map(|node| node.bounding_box().surface_area()).collect();
+        result.built_cost = result.cost();
+        result.build_time = start.elapsed();
+        result
+    }
+
+    //the tree saved for objects with the same boxes, None when there is none or the file doesn't match
+    pub fn load(path: &Path, objects: &[Rc<dyn Hittable>], t0: Float, t1: Float) -> Option<Self> {
+        let start = Instant::now();
+        let file = CacheFile::open(path, geometry_key(objects, t0, t1))?;
+        let bounded = file.order().count();
+
+        if bounded + file.unbounded().count() != objects.len() || !Self::linked(file.nodes(), bounded) {
+            return None;
+        }
+
+        let order: Vec<usize> = file.order().collect();
+        let unbounded: Vec<usize> = file.unbounded().collect();
+        let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+
+        Some(Self::assemble(objects, NodeStorage::Mapped(file), order.into_iter(), unbounded.into_iter(), threads, start))
+    }
+
+    //objects must be the ones the tree was built over, they are stored as indices into them
+    pub fn save(&self, path: &Path, objects: &[Rc<dyn Hittable>], t0: Float, t1: Float) -> io::Result<()> {
+        let indices: HashMap<*const (), usize> = objects.iter().enumerate()
+            .map(|(idx, object)| (Rc::as_ptr(object) as *const (), idx))
+            .collect();
+
+        let index_of = |object: &Rc<dyn Hittable>| indices.get(&(Rc::as_ptr(object) as *const ())).copied();
+        let order: Option<Vec<usize>> = self.objects.iter().map(index_of).collect();
+        let unbounded: Option<Vec<usize>> = self.unbounded.iter().map(index_of).collect();
+
+        match (order, unbounded) {
+            (Some(order), Some(unbounded)) => cache::write(path, geometry_key(objects, t0, t1), &self.nodes, &order, &unbounded),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidInput, "The BVH was not built over these objects"))
+        }
+    }
+
+    //loads the tree saved for these objects, or builds it and saves it for the next run
+    pub fn cached(path: &Path, objects: &[Rc<dyn Hittable>], t0: Float, t1: Float) -> io::Result<Self> {
+        if let Some(result) = Self::load(path, objects, t0, t1) {
+            return Ok(result);
+        }
+
+        let result = Self::new(objects, t0, t1);
+        result.save(path, objects, t0, t1)?;
+        Ok(result)
+    }
+
+    //a damaged file can pass every header check, the links must stay inside the arrays and point forward like the builder's
+    fn linked(nodes: &[LinearNode], objects: usize) -> bool {
+        nodes.iter().enumerate().all(|(idx, node)| {
+            let offset = node.offset as usize;
+
+            node.axis < 3 && if node.count > 0 {
+                offset + node.count as usize <= objects
+            }
+            else {
+                idx + 1 < offset && offset < nodes.len()
+            }
+        })
+    }
+
+    fn split(items: &mut [(usize, BoundingBox)], depth: usize) -> Option<(usize, usize)> {
+        if depth < MAX_SAH_DEPTH {
+            sah_partition(items, MAX_LEAF_SIZE)
+        }
+        else if items.len() > 1 {
+            Some((items.len() / 2, 0))
+        }
+        else {
+            None
+        }
+    }
+
+    fn enclosing_box(items: &[(usize, BoundingBox)]) -> BoundingBox {
+        items[1..].iter().fold(items[0].1, |acc, (_, obj_box)| BoundingBox::surrounding_box(&acc, obj_box))
+    }
+
+    fn build(items: &mut [(usize, BoundingBox)], nodes: &mut Vec<LinearNode>, offset: usize, depth: usize) -> usize {
+        let obj_box = Self::enclosing_box(items);
+        let idx = nodes.len();
+
+        match Self::split(items, depth) {
+            Some((mid, axis)) => {
+                nodes.push(LinearNode::new(&obj_box, 0, 0, axis));
+
+                let (left, right) = items.split_at_mut(mid);
+                Self::build(left, nodes, offset, depth + 1);
+                let right = Self::build(right, nodes, offset + mid, depth + 1);
+
+                nodes[idx].offset = right as u32;
+            },
+            None => nodes.push(LinearNode::new(&obj_box, offset, items.len(), 0))
+        }
+
+        idx
+    }
+
+    //the left subtree goes to a new thread and the right one stays on this one, each in its own array appended after the parent
+    //the split is the same as the serial one so the tree doesn't depend on the number of threads
+    fn build_parallel(items: &mut [(usize, BoundingBox)], offset: usize, depth: usize, threads: usize) -> Vec<LinearNode> {
+        let mut nodes = vec![];
+
+        if threads <= 1 || items.len() < PARALLEL_THRESHOLD {
+            nodes.reserve(2 * items.len());
+            Self::build(items, &mut nodes, offset, depth);
+            return nodes;
+        }
+
+        let obj_box = Self::enclosing_box(items);
+
+        let (mid, axis) = match Self::split(items, depth) {
+            Some(split) => split,
+            None => {
+                nodes.push(LinearNode::new(&obj_box, offset, items.len(), 0));
+                return nodes;
+            }
+        };
+
+        let (left, right) = items.split_at_mut(mid);
+
+        let (left, right) = thread::scope(|scope| {
+            let left = scope.spawn(|| Self::build_parallel(left, offset, depth + 1, threads / 2));
+            let right = Self::build_parallel(right, offset + mid, depth + 1, threads - threads / 2);
+
+            (left.join().expect("A BVH build thread panicked"), right)
+        });
+
+        nodes.reserve(1 + left.len() + right.len());
+        nodes.push(LinearNode::new(&obj_box, 1 + left.len(), 0, axis));
+        Self::append(&mut nodes, left);
+        Self::append(&mut nodes, right);
+        nodes
+    }
+
+    fn append(nodes: &mut Vec<LinearNode>, subtree: Vec<LinearNode>) {
+        let base = nodes.len() as u32;
+
+        nodes.extend(subtree.into_iter().map(|mut node| {
+            if node.count == 0 {
+                node.offset += base;
+            }
+            node
+        }));
+    }
+
+    //expected cost of a ray through the tree relative to one intersection, the surface area heuristic
+    pub fn cost(&self) -> Float {
+        let root_area = match self.nodes.first() {
+            Some(node) => node.bounding_box().surface_area().max(Float::MIN_POSITIVE),
+            None => return 0.0
+        };
+
+        self.nodes.iter()
+            .map(|node| {
+                let weight = if node.count > 0 { node.count as Float } else { TRAVERSAL_COST };
+                weight * node.bounding_box().surface_area() / root_area
+            })
+            .sum()
+    }
+
+    //recomputes every box for the new interval without touching the structure, children always come after their parent
+    //a leaf object without a box is the only way to fail, so it's checked before any node is written
+    pub fn refit(&mut self, t0: Float, t1: Float) -> bool {
+        if self.objects.iter().any(|object| object.bounding_box(t0, t1).is_none()) {
+            return false;
+        }
+
+        let nodes = self.nodes.to_mut();
+
+        for idx in (0..nodes.len()).rev() {
+            let node = nodes[idx];
+
+            let obj_box = if node.count > 0 {
+                let start = node.offset as usize;
+
+                self.objects[start..start + node.count as usize].iter()
+                    .map(|object| object.bounding_box(t0, t1).unwrap())
+                    .reduce(|acc, obj_box| BoundingBox::surrounding_box(&acc, &obj_box))
+                    .unwrap()
+            }
+            else {
+                BoundingBox::surrounding_box(&nodes[idx + 1].bounding_box(), &nodes[node.offset as usize].bounding_box())
+            };
+
+            nodes[idx] = LinearNode::new(&obj_box, node.offset as usize, node.count as usize, node.axis as usize);
+        }
+
+        true
+    }
+
+    //refits, then rebuilds the subtrees that degraded or everything if the tree as a whole did
+    pub fn update(&mut self, t0: Float, t1: Float) -> BvhUpdate {
+        if !self.refit(t0, t1) || self.cost() > self.built_cost * COST_GROWTH {
+            self.rebuild(t0, t1);
+            return BvhUpdate::Rebuilt;
+        }
+
+        if self.nodes.is_empty() {
+            return BvhUpdate::Refitted;
+        }
+
+        let old_nodes = std::mem::take(&mut self.nodes);
+        let old_areas = std::mem::take(&mut self.built_areas);
+        let mut rebuilt = 0;
+
+        self.relink(&old_nodes, &old_areas, 0, 0, &mut rebuilt, t0, t1);
+
+        if rebuilt == 0 { BvhUpdate::Refitted } else { BvhUpdate::PartiallyRebuilt(rebuilt) }
+    }
+
+    pub fn rebuild(&mut self, t0: Float, t1: Float) {
+        let objects: Vec<Rc<dyn Hittable>> = self.objects.iter().chain(self.unbounded.iter()).cloned().collect();
+        *self = Self::with_threads(&objects, t0, t1, self.threads);
+    }
+
+    //copies the old tree in order, degraded subtrees are built again in place of the copy so every link is written once
+    #[allow(clippy::too_many_arguments)]
+    fn relink(&mut self, old_nodes: &[LinearNode], old_areas: &[Float], idx: usize, depth: usize, rebuilt: &mut usize, t0: Float, t1: Float) -> usize {
+        let node = old_nodes[idx];
+        let new_idx = self.nodes.len();
+
+        if node.count == 0 && node.bounding_box().surface_area() > old_areas[idx] * SUBTREE_GROWTH {
+            let (start, end) = Self::object_range(old_nodes, idx);
+            let mut items: Vec<(usize, BoundingBox)> = (start..end)
+                .map(|object| (object, self.objects[object].bounding_box(t0, t1).unwrap()))
+                .collect();
+
+            Self::build(&mut items, self.nodes.to_mut(), start, depth);
+
+            let reordered: Vec<Rc<dyn Hittable>> = items.iter().map(|(object, _)| self.objects[*object].clone()).collect();
+            self.objects[start..end].clone_from_slice(&reordered);
+
+            let areas: Vec<Float> = self.nodes[new_idx..].iter().map(|node| node.bounding_box().surface_area()).collect();
+            self.built_areas.extend(areas);
+            *rebuilt += 1;
+
+            return new_idx;
+        }
+
+        self.nodes.to_mut().push(node);
+        self.built_areas.push(old_areas[idx]);
+
+        if node.count == 0 {
+            self.relink(old_nodes, old_areas, idx + 1, depth + 1, rebuilt, t0, t1);
+            let right = self.relink(old_nodes, old_areas, node.offset as usize, depth + 1, rebuilt, t0, t1);
+
+            self.nodes.to_mut()[new_idx].offset = right as u32;
+        }
+
+        new_idx
+    }
+
+    fn object_range(nodes: &[LinearNode], idx: usize) -> (usize, usize) {
+        let mut first = idx;
+        while nodes[first].count == 0 {
+            first += 1;
+        }
+
+        let mut last = idx;
+        while nodes[last].count == 0 {
+            last = nodes[last].offset as usize;
+        }
+
+        (nodes[first].offset as usize, nodes[last].offset as usize + nodes[last].count as usize)
+    }
+
+    pub fn stats(&self) -> BuildStats {
+        let mut depths = vec![0; self.nodes.len()];
+        let mut leaves = 0;
+
+        for (idx, node) in self.nodes.iter().enumerate() {
+            if node.count > 0 {
+                leaves += 1;
+            }
+            else {
+                depths[idx + 1] = depths[idx] + 1;
+                depths[node.offset as usize] = depths[idx] + 1;
+            }
+        }
+
+        BuildStats {
+            time: self.build_time,
+            threads: self.threads,
+            nodes: self.nodes.len(),
+            leaves,
+            max_depth: depths.into_iter().max().unwrap_or(0),
+            average_leaf_size: if leaves > 0 { self.objects.len() as Float / leaves as Float } else { 0.0 },
+            cost: self.cost()
+        }
+    }
+
+    pub fn nodes(&self) -> &[LinearNode] {
+        &self.nodes
+    }
+
+    pub fn objects(&self) -> &[Rc<dyn Hittable>] {
+        &self.objects
+    }
+}
+
+impl Hittable for LinearBvh {
+    fn hit(&self, ray: &Ray, tmin: Float, mut tmax: Float) -> Option<HitRecord> {
+        let mut result = None;
+
+        for object in self.unbounded.iter() {
+            if let Some(record) = object.hit(ray, tmin, tmax) {
+                tmax = record.time;
+                result = Some(record);
+            }
+        }
+
+        if self.nodes.is_empty() {
+            return result;
+        }
+
+        let inverse_direction = Vec3::new(1.0 / ray.direction.x, 1.0 / ray.direction.y, 1.0 / ray.direction.z);
+        let negative = [inverse_direction.x < 0.0, inverse_direction.y < 0.0, inverse_direction.z < 0.0];
+
+        let mut stack = [0usize; STACK_SIZE];
+        let mut size = 0;
+        let mut current = 0;
+
+        loop {
+            let node = &self.nodes[current];
+
+            if node.hit(&ray.origin, &inverse_direction, tmin, tmax) {
+                if node.count > 0 {
+                    let start = node.offset as usize;
+
+                    for object in self.objects[start..start + node.count as usize].iter() {
+                        if let Some(record) = object.hit(ray, tmin, tmax) {
+                            tmax = record.time;
+                            result = Some(record);
+                        }
+                    }
+                }
+                //the far child waits on the stack and is culled by tmax if something closer was hit
+                else if negative[node.axis as usize] {
+                    stack[size] = current + 1;
+                    size += 1;
+                    current = node.offset as usize;
+                    continue;
+                }
+                else {
+                    stack[size] = node.offset as usize;
+                    size += 1;
+                    current += 1;
+                    continue;
+                }
+            }
+
+            if size == 0 {
+                break;
+            }
+
+            size -= 1;
+            current = stack[size];
+        }
+
+        result
+    }
+
+    //no near child ordering, the interval never shrinks so any order finds the first blocker as fast
+    fn occluded(&self, ray: &Ray, tmin: Float, tmax: Float) -> bool {
+        if self.unbounded.iter().any(|object| object.occluded(ray, tmin, tmax)) {
+            return true;
+        }
+
+        if self.nodes.is_empty() {
+            return false;
+        }
+
+        let inverse_direction = Vec3::new(1.0 / ray.direction.x, 1.0 / ray.direction.y, 1.0 / ray.direction.z);
+
+        let mut stack = [0usize; STACK_SIZE];
+        let mut size = 0;
+        let mut current = 0;
+
+        loop {
+            let node = &self.nodes[current];
+
+            if node.hit(&ray.origin, &inverse_direction, tmin, tmax) {
+                if node.count > 0 {
+                    let start = node.offset as usize;
+
+                    if self.objects[start..start + node.count as usize].iter().any(|object| object.occluded(ray, tmin, tmax)) {
+                        return true;
+                    }
+                }
+                else {
+                    stack[size] = node.offset as usize;
+                    size += 1;
+                    current += 1;
+                    continue;
+                }
+            }
+
+            if size == 0 {
+                return false;
+            }
+
+            size -= 1;
+            current = stack[size];
+        }
+    }
+
+    fn bounding_box(&self, _t0: Float, _t1: Float) -> Option<BoundingBox> {
+        if !self.unbounded.is_empty() {
+            return None;
+        }
+
+        self.nodes.first().map(|node| node.bounding_box())
+    }
+}
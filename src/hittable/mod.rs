@@ -1,7 +1,16 @@
+#[allow(clippy::module_inception)]
 pub mod hittable;
 pub mod bounding;
+pub mod animated;
+pub mod linear;
+pub mod cache;
+pub mod instance;
+pub mod qbvh;
+pub mod grid;
+pub mod kdtree;
+pub mod accelerator;
 
-use super::utils::{ray, vec3, min_f64, max_f64};
+use super::utils::{ray, vec3, color, quaternion, min_float, max_float, f32_above, f32_below, gamma, Float};
 use super::material;
 
 pub use hittable::*;
\ No newline at end of file
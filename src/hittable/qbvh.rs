@@ -0,0 +1,509 @@
+use super::bounding::{BoundingBox, sah_partition};
+use super::hittable::{HitRecord, Hittable};
+use super::{f32_above, f32_below};
+use super::vec3::Vec3;
+use super::ray::Ray;
+use super::Float;
+
+use std::mem::MaybeUninit;
+use std::rc::Rc;
+
+const WIDTH: usize = 4;
+const MAX_LEAF_SIZE: usize = 4;
+//every node pushes at most three children, the depth limit keeps the stack bounded
+const STACK_SIZE: usize = 256;
+const MAX_SAH_DEPTH: usize = 64;
+const PACKET_SIZE: usize = 64;
+//pbrt's gamma(3), covers the rounding of the single precision slab test
+const SLAB_ERROR: f32 = 1.0 + 2.0 * (3.0 * f32::EPSILON / 2.0) / (1.0 - 3.0 * f32::EPSILON / 2.0);
+
+//four child boxes side by side so one slab test covers all of them, empty slots have inverted boxes and are never hit
+//children are node indices when their count is 0 and offsets in the objects otherwise
+#[derive(Clone, Copy)]
+#[repr(C, align(16))]
+pub struct QuadNode {
+    min: [[f32; WIDTH]; 3],
+    max: [[f32; WIDTH]; 3],
+    children: [u32; WIDTH],
+    counts: [u16; WIDTH]
+}
+
+impl QuadNode {
+    fn empty() -> Self {
+        Self {
+            min: [[f32::INFINITY; WIDTH]; 3],
+            max: [[f32::NEG_INFINITY; WIDTH]; 3],
+            children: [0; WIDTH],
+            counts: [0; WIDTH]
+        }
+    }
+
+    fn set(&mut self, slot: usize, obj_box: &BoundingBox, child: usize, count: usize) {
+        for axis in 0..3 {
+            self.min[axis][slot] = f32_below(obj_box.min[axis]);
+            self.max[axis][slot] = f32_above(obj_box.max[axis]);
+        }
+
+        self.children[slot] = child as u32;
+        self.counts[slot] = count as u16;
+    }
+
+    pub fn bounding_box(&self, slot: usize) -> Option<BoundingBox> {
+        if self.min[0][slot] > self.max[0][slot] {
+            return None;
+        }
+
+        Some(BoundingBox::new(
+            Vec3::new(self.min[0][slot] as Float, self.min[1][slot] as Float, self.min[2][slot] as Float),
+            Vec3::new(self.max[0][slot] as Float, self.max[1][slot] as Float, self.max[2][slot] as Float)
+        ))
+    }
+}
+
+//the ray as the slab test wants it, the origin is rounded both ways so the single precision distances stay conservative
+struct SlabRay {
+    near_origin: [f32; 3],
+    far_origin: [f32; 3],
+    inverse_direction: [f32; 3],
+    negative: [bool; 3]
+}
+
+impl SlabRay {
+    #[allow(clippy::unnecessary_cast)]
+    fn new(ray: &Ray) -> Self {
+        let mut result = Self {
+            near_origin: [0.0; 3],
+            far_origin: [0.0; 3],
+            inverse_direction: [0.0; 3],
+            negative: [false; 3]
+        };
+
+        for axis in 0..3 {
+            let inverse = 1.0 / ray.direction[axis];
+            let (below, above) = (f32_below(ray.origin[axis]), f32_above(ray.origin[axis]));
+
+            result.inverse_direction[axis] = inverse as f32;
+            result.negative[axis] = inverse < 0.0;
+
+            //the min plane always takes the origin rounded up and the max plane the one rounded down
+            (result.near_origin[axis], result.far_origin[axis]) = if inverse < 0.0 { (below, above) } else { (above, below) };
+        }
+
+        result
+    }
+
+    fn planes<'a>(&self, node: &'a QuadNode, axis: usize) -> (&'a [f32; WIDTH], &'a [f32; WIDTH]) {
+        if self.negative[axis] { (&node.max[axis], &node.min[axis]) } else { (&node.min[axis], &node.max[axis]) }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+fn slab_test(node: &QuadNode, ray: &SlabRay, tmin: f32, tmax: f32) -> (u32, [f32; WIDTH]) {
+    use std::arch::x86_64::*;
+
+    //sse is enabled on every x86_64 target so the intrinsics are always there, no runtime detection needed
+    unsafe {
+        //the planes of an axis are 16 byte aligned inside the node
+        let load = |values: &[f32; WIDTH]| _mm_load_ps(values.as_ptr());
+
+        let mut near = _mm_set1_ps(tmin);
+        let mut far = _mm_set1_ps(tmax);
+
+        for axis in 0..3 {
+            let (near_planes, far_planes) = ray.planes(node, axis);
+            let inverse = _mm_set1_ps(ray.inverse_direction[axis]);
+
+            let t0 = _mm_mul_ps(_mm_sub_ps(load(near_planes), _mm_set1_ps(ray.near_origin[axis])), inverse);
+            let t1 = _mm_mul_ps(_mm_sub_ps(load(far_planes), _mm_set1_ps(ray.far_origin[axis])), inverse);
+
+            //nan slabs, a plane through an origin parallel to it, take the second operand and drop out
+            near = _mm_max_ps(t0, near);
+            far = _mm_min_ps(_mm_mul_ps(t1, _mm_set1_ps(SLAB_ERROR)), far);
+        }
+
+        let mut distances = [0.0; WIDTH];
+        let mask = _mm_movemask_ps(_mm_cmple_ps(near, far)) as u32;
+        _mm_storeu_ps(distances.as_mut_ptr(), near);
+
+        (mask, distances)
+    }
+}
+
+//same test one lane at a time, written so the compiler can still vectorize it
+#[cfg(not(target_arch = "x86_64"))]
+fn slab_test(node: &QuadNode, ray: &SlabRay, tmin: f32, tmax: f32) -> (u32, [f32; WIDTH]) {
+    let mut near = [tmin; WIDTH];
+    let mut far = [tmax; WIDTH];
+
+    for axis in 0..3 {
+        let (near_planes, far_planes) = ray.planes(node, axis);
+
+        for slot in 0..WIDTH {
+            let t0 = (near_planes[slot] - ray.near_origin[axis]) * ray.inverse_direction[axis];
+            let t1 = (far_planes[slot] - ray.far_origin[axis]) * ray.inverse_direction[axis] * SLAB_ERROR;
+
+            near[slot] = if t0 > near[slot] { t0 } else { near[slot] };
+            far[slot] = if t1 < far[slot] { t1 } else { far[slot] };
+        }
+    }
+
+    let mask = (0..WIDTH).fold(0, |mask, slot| if near[slot] <= far[slot] { mask | 1 << slot } else { mask });
+    (mask, near)
+}
+
+//a child slot waiting on the traversal stack
+#[derive(Clone, Copy, Default)]
+struct Entry {
+    child: u32,
+    count: u16,
+    distance: f32
+}
+
+pub struct Qbvh {
+    objects: Vec<Rc<dyn Hittable>>,
+    nodes: Vec<QuadNode>,
+    unbounded: Vec<Rc<dyn Hittable>>
+}
+
+impl Qbvh {
+    pub fn new(objects: &[Rc<dyn Hittable>], t0: Float, t1: Float) -> Self {
+        let mut items = vec![];
+        let mut unbounded = vec![];
+
+        for object in objects.iter() {
+            match object.bounding_box(t0, t1) {
+                Some(obj_box) => items.push((object.clone(), obj_box)),
+                None => unbounded.push(object.clone())
+            }
+        }
+
+        let mut nodes = Vec::with_capacity(items.len() / 2 + 1);
+
+        if !items.is_empty() {
+            let root_box = Self::enclosing_box(&items);
+            let (child, count) = Self::child(&mut items, &mut nodes, 0, 0);
+
+            //a node is pushed before its children so the root is always first, a single leaf gets wrapped in one
+            if count > 0 {
+                let mut root = QuadNode::empty();
+                root.set(0, &root_box, child, count);
+                nodes.push(root);
+            }
+        }
+
+        Self {
+            objects: items.into_iter().map(|(object, _)| object).collect(),
+            nodes,
+            unbounded
+        }
+    }
+
+    fn enclosing_box(items: &[(Rc<dyn Hittable>, BoundingBox)]) -> BoundingBox {
+        items[1..].iter().fold(items[0].1, |acc, (_, obj_box)| BoundingBox::surrounding_box(&acc, obj_box))
+    }
+
+    fn partition(items: &mut [(Rc<dyn Hittable>, BoundingBox)], depth: usize) -> Option<usize> {
+        if depth < MAX_SAH_DEPTH {
+            sah_partition(items, MAX_LEAF_SIZE).map(|(mid, _)| mid)
+        }
+        else if items.len() > MAX_LEAF_SIZE {
+            Some(items.len() / 2)
+        }
+        else {
+            None
+        }
+    }
+
+    //returns the slot contents for these items, a node index with count 0 or a leaf
+    fn child(items: &mut [(Rc<dyn Hittable>, BoundingBox)], nodes: &mut Vec<QuadNode>, offset: usize, depth: usize) -> (usize, usize) {
+        let mid = match Self::partition(items, depth) {
+            Some(mid) => mid,
+            None => return (offset, items.len())
+        };
+
+        //keeps splitting the largest child until the four slots are used, a binary tree collapsed in place
+        let mut ranges = vec![(0, mid, false), (mid, items.len(), false)];
+
+        while ranges.len() < WIDTH {
+            let largest = ranges.iter().enumerate()
+                .filter(|(_, (_, _, leaf))| !leaf)
+                .map(|(idx, (start, end, _))| (idx, Self::enclosing_box(&items[*start..*end]).surface_area()))
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(idx, _)| idx);
+
+            let idx = match largest {
+                Some(idx) => idx,
+                None => break
+            };
+
+            let (start, end, _) = ranges[idx];
+
+            match Self::partition(&mut items[start..end], depth + 1) {
+                Some(mid) => {
+                    ranges[idx] = (start, start + mid, false);
+                    ranges.insert(idx + 1, (start + mid, end, false));
+                },
+                None => ranges[idx].2 = true
+            }
+        }
+
+        let idx = nodes.len();
+        nodes.push(QuadNode::empty());
+
+        for (slot, (start, end, leaf)) in ranges.into_iter().enumerate() {
+            let obj_box = Self::enclosing_box(&items[start..end]);
+
+            let (child, count) = if leaf {
+                (offset + start, end - start)
+            }
+            else {
+                Self::child(&mut items[start..end], nodes, offset + start, depth + 1)
+            };
+
+            nodes[idx].set(slot, &obj_box, child, count);
+        }
+
+        (idx, 0)
+    }
+
+    pub fn nodes(&self) -> &[QuadNode] {
+        &self.nodes
+    }
+
+    pub fn objects(&self) -> &[Rc<dyn Hittable>] {
+        &self.objects
+    }
+
+    //traces coherent rays together, each node is fetched once for the whole packet and tested only against the rays still inside it
+    //it pays off once the tree no longer fits in the cache, on small scenes single rays are faster
+    pub fn hit_packet(&self, rays: &[Ray], tmin: Float, tmax: Float) -> Vec<Option<HitRecord>> {
+        let mut result: Vec<Option<HitRecord>> = Vec::with_capacity(rays.len());
+
+        for packet in rays.chunks(PACKET_SIZE) {
+            let mut records: Vec<Option<HitRecord>> = vec![None; packet.len()];
+            let mut limits = vec![tmax; packet.len()];
+
+            for (idx, ray) in packet.iter().enumerate() {
+                for object in self.unbounded.iter() {
+                    if let Some(record) = object.hit(ray, tmin, limits[idx]) {
+                        limits[idx] = record.time;
+                        records[idx] = Some(record);
+                    }
+                }
+            }
+
+            if !self.nodes.is_empty() {
+                self.traverse_packet(packet, tmin, &mut limits, &mut records);
+            }
+
+            result.extend(records);
+        }
+
+        result
+    }
+
+    fn traverse_packet(&self, packet: &[Ray], tmin: Float, limits: &mut [Float], records: &mut [Option<HitRecord>]) {
+        let slab_rays: Vec<SlabRay> = packet.iter().map(SlabRay::new).collect();
+        let active = if packet.len() == PACKET_SIZE { u64::MAX } else { (1u64 << packet.len()) - 1 };
+
+        let mut stack = [(0u32, 0u16, 0u64); STACK_SIZE];
+        stack[0] = (0, 0, active);
+        let mut size = 1;
+
+        while size > 0 {
+            size -= 1;
+            let (child, count, mask) = stack[size];
+
+            if count > 0 {
+                let start = child as usize;
+
+                for object in self.objects[start..start + count as usize].iter() {
+                    let mut rays = mask;
+
+                    while rays != 0 {
+                        let idx = rays.trailing_zeros() as usize;
+                        rays &= rays - 1;
+
+                        if let Some(record) = object.hit(&packet[idx], tmin, limits[idx]) {
+                            limits[idx] = record.time;
+                            records[idx] = Some(record);
+                        }
+                    }
+                }
+
+                continue;
+            }
+
+            let node = &self.nodes[child as usize];
+            let mut masks = [0u64; WIDTH];
+            let mut distances = [f32::INFINITY; WIDTH];
+            let mut rays = mask;
+            let leader = mask.trailing_zeros() as usize;
+
+            while rays != 0 {
+                let idx = rays.trailing_zeros() as usize;
+                rays &= rays - 1;
+
+                let (hits, near) = slab_test(node, &slab_rays[idx], f32_below(tmin), f32_above(limits[idx]));
+
+                for (slot, slot_mask) in masks.iter_mut().enumerate() {
+                    if hits & (1 << slot) != 0 {
+                        *slot_mask |= 1 << idx;
+                    }
+                }
+
+                //the first ray of the packet decides the order, coherent rays agree on it
+                if idx == leader {
+                    distances = near;
+                }
+            }
+
+            let hits = (0..WIDTH).fold(0, |hits, slot| if masks[slot] != 0 { hits | 1 << slot } else { hits });
+            let (order, count) = Self::far_to_near(hits, &distances);
+
+            for slot in order[..count].iter() {
+                stack[size] = (node.children[*slot], node.counts[*slot], masks[*slot]);
+                size += 1;
+            }
+        }
+    }
+
+    //the slots in the mask sorted by decreasing distance, an insertion sort since there are rarely more than two
+    fn far_to_near(mask: u32, distances: &[f32; WIDTH]) -> ([usize; WIDTH], usize) {
+        let mut order = [0; WIDTH];
+        let mut size = 0;
+
+        for slot in 0..WIDTH {
+            if mask & (1 << slot) == 0 {
+                continue;
+            }
+
+            let mut idx = size;
+            while idx > 0 && distances[order[idx - 1]] < distances[slot] {
+                order[idx] = order[idx - 1];
+                idx -= 1;
+            }
+
+            order[idx] = slot;
+            size += 1;
+        }
+
+        (order, size)
+    }
+}
+
+impl Hittable for Qbvh {
+    fn hit(&self, ray: &Ray, tmin: Float, mut tmax: Float) -> Option<HitRecord> {
+        let mut result = None;
+
+        for object in self.unbounded.iter() {
+            if let Some(record) = object.hit(ray, tmin, tmax) {
+                tmax = record.time;
+                result = Some(record);
+            }
+        }
+
+        if self.nodes.is_empty() {
+            return result;
+        }
+
+        let slab_ray = SlabRay::new(ray);
+        let near_limit = f32_below(tmin);
+        let mut far_limit = f32_above(tmax);
+
+        //only the entries below size are ever read, clearing the whole stack would cost as much as the traversal of a small scene
+        let mut stack = [MaybeUninit::<Entry>::uninit(); STACK_SIZE];
+        stack[0].write(Entry::default());
+        let mut size = 1;
+
+        while size > 0 {
+            size -= 1;
+            let entry = unsafe { stack[size].assume_init() };
+
+            //pushed before something closer was hit
+            if entry.distance > far_limit {
+                continue;
+            }
+
+            if entry.count > 0 {
+                let start = entry.child as usize;
+
+                for object in self.objects[start..start + entry.count as usize].iter() {
+                    if let Some(record) = object.hit(ray, tmin, tmax) {
+                        tmax = record.time;
+                        far_limit = f32_above(tmax);
+                        result = Some(record);
+                    }
+                }
+
+                continue;
+            }
+
+            let node = &self.nodes[entry.child as usize];
+            let (mask, distances) = slab_test(node, &slab_ray, near_limit, far_limit);
+
+            //nearest child on top of the stack
+            let (order, count) = Self::far_to_near(mask, &distances);
+
+            for slot in order[..count].iter() {
+                stack[size].write(Entry { child: node.children[*slot], count: node.counts[*slot], distance: distances[*slot] });
+                size += 1;
+            }
+        }
+
+        result
+    }
+
+    fn occluded(&self, ray: &Ray, tmin: Float, tmax: Float) -> bool {
+        if self.unbounded.iter().any(|object| object.occluded(ray, tmin, tmax)) {
+            return true;
+        }
+
+        if self.nodes.is_empty() {
+            return false;
+        }
+
+        let slab_ray = SlabRay::new(ray);
+        let (near_limit, far_limit) = (f32_below(tmin), f32_above(tmax));
+
+        let mut stack = [MaybeUninit::<Entry>::uninit(); STACK_SIZE];
+        stack[0].write(Entry::default());
+        let mut size = 1;
+
+        while size > 0 {
+            size -= 1;
+            let entry = unsafe { stack[size].assume_init() };
+
+            if entry.count > 0 {
+                let start = entry.child as usize;
+
+                if self.objects[start..start + entry.count as usize].iter().any(|object| object.occluded(ray, tmin, tmax)) {
+                    return true;
+                }
+
+                continue;
+            }
+
+            let node = &self.nodes[entry.child as usize];
+            let (mask, _) = slab_test(node, &slab_ray, near_limit, far_limit);
+
+            for slot in 0..WIDTH {
+                if mask & (1 << slot) != 0 {
+                    stack[size].write(Entry { child: node.children[slot], count: node.counts[slot], distance: 0.0 });
+                    size += 1;
+                }
+            }
+        }
+
+        false
+    }
+
+    fn bounding_box(&self, _t0: Float, _t1: Float) -> Option<BoundingBox> {
+        if !self.unbounded.is_empty() {
+            return None;
+        }
+
+        let root = self.nodes.first()?;
+        (0..WIDTH).filter_map(|slot| root.bounding_box(slot)).reduce(|acc, obj_box| BoundingBox::surrounding_box(&acc, &obj_box))
+    }
+}
@@ -3,22 +3,23 @@ use super::hittable::HitRecord;
 use super::utils::color::Color;
 use super::material::Material;
 use super::utils::vec3::Vec3;
-use super::utils::min_f64;
+use super::utils::min_float;
+use super::utils::Float;
 
 use rand::Rng;
 
 pub struct Dielectric {
-    refraction: f64
+    refraction: Float
 }
 
 impl Dielectric {
-    pub fn new(refraction: f64) -> Self {
+    pub fn new(refraction: Float) -> Self {
         Self {
             refraction
         }
     }
 
-    fn schlick(cos: f64, index: f64) -> f64 {
+    fn schlick(cos: Float, index: Float) -> Float {
         let mut r0 = (1.0 - index) / (1.0 + index);
         r0 = r0 * r0;
 
@@ -31,7 +32,7 @@ impl Material for Dielectric {
         let index = if record.front_face { 1.0 / self.refraction } else { self.refraction };
         let unit_direction = ray.direction.unit_vector();
 
-        let cos = min_f64(Vec3::dot_product(-unit_direction, record.normal), 1.0);
+        let cos = min_float(Vec3::dot_product(-unit_direction, record.normal), 1.0);
         let sin = (1.0 - cos * cos).sqrt();
 
         let reflect_prob = Self::schlick(cos, index);
@@ -0,0 +1,122 @@
+use super::utils::ray::{Ray, ScatteredRay};
+use super::hittable::HitRecord;
+use super::utils::color::Color;
+use super::material::Material;
+use super::utils::vec3::Vec3;
+use super::utils::Float;
+use super::utils::consts::PI;
+
+use num::clamp;
+use rand::Rng;
+
+pub struct Hair {
+    absorption: Color,
+    refraction: Float,
+    shifts: [Float; 3],
+    widths: [Float; 3],
+}
+
+impl Hair {
+    //roughness and tilt are the longitudinal width and the cuticle tilt of the R lobe, in degrees
+    pub fn new(absorption: Color, refraction: Float, roughness: Float, tilt: Float) -> Self {
+        let shift = -tilt.to_radians();
+        let width = roughness.to_radians();
+
+        Self {
+            absorption,
+            refraction,
+            shifts: [2.0 * shift, -shift, -3.0 * shift],
+            widths: [width, width / 2.0, 2.0 * width],
+        }
+    }
+
+    pub fn from_melanin(concentration: Float, roughness: Float) -> Self {
+        Self::new(Color::new(0.419, 0.697, 1.37) * concentration, 1.55, roughness, 3.0)
+    }
+
+    fn fresnel(cos: Float, refraction: Float) -> Float {
+        let mut r0 = (1.0 - refraction) / (1.0 + refraction);
+        r0 = r0 * r0;
+
+        r0 + (1.0 - r0) * (1.0 - cos).powi(5)
+    }
+
+    fn gaussian<R: Rng>(rng: &mut R) -> Float {
+        let u1: Float = rng.gen_range(1e-12, 1.0);
+        let u2: Float = rng.gen_range(0.0, 1.0);
+
+        (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+    }
+}
+
+impl Material for Hair {
+    fn scatter(&self, ray: &Ray, record: &HitRecord) -> Option<ScatteredRay> {
+        let mut rng = rand::thread_rng();
+        let tangent = if record.dpdu.squared_length() > 0.0 { record.dpdu.unit_vector() } else { return None };
+
+        let wo = -ray.direction.unit_vector();
+        let sin_theta_o = clamp(Vec3::dot_product(wo, tangent), -1.0, 1.0);
+        let cos_theta_o = (1.0 - sin_theta_o * sin_theta_o).sqrt();
+
+        if cos_theta_o < 1e-6 {
+            return None;
+        }
+
+        //azimuths are measured around the fiber starting from the viewing direction
+        let y = (wo - tangent * sin_theta_o).unit_vector();
+        let z = Vec3::cross_product(tangent, y);
+
+        //cylindrical curves bend the normal by the offset across the fiber, flat ones only have v
+        let offset = Vec3::dot_product(record.normal, z);
+        let h = clamp(if offset.abs() > 1e-9 { -offset } else { 2.0 * record.v - 1.0 }, -1.0, 1.0);
+        let gamma_o = h.asin();
+
+        let eta = (self.refraction * self.refraction - sin_theta_o * sin_theta_o).sqrt() / cos_theta_o;
+        let gamma_t = clamp(h / eta, -1.0, 1.0).asin();
+        let cos_theta_t = (1.0 - (sin_theta_o / self.refraction).powi(2)).max(0.0).sqrt();
+
+        let path = 2.0 * gamma_t.cos() / cos_theta_t.max(1e-6);
+        let transmittance = Color::new(
+            (-self.absorption.r * path).exp(),
+            (-self.absorption.g * path).exp(),
+            (-self.absorption.b * path).exp()
+        );
+
+        let f = Self::fresnel(cos_theta_o * gamma_o.cos(), self.refraction);
+        let white = Color::new(1.0, 1.0, 1.0);
+        let lobes = [
+            white * f,
+            transmittance * (1.0 - f) * (1.0 - f),
+            transmittance * transmittance * (1.0 - f) * (1.0 - f) * f
+        ];
+
+        let weights: Vec<Float> = lobes.iter().map(|c| (c.r + c.g + c.b) / 3.0).collect();
+        let total: Float = weights.iter().sum();
+
+        if total <= 0.0 {
+            return None;
+        }
+
+        let mut target = rng.gen_range(0.0, total);
+        let mut lobe = 2;
+        for (idx, weight) in weights.iter().enumerate() {
+            if target < *weight {
+                lobe = idx;
+                break;
+            }
+            target -= weight;
+        }
+
+        let theta_o = sin_theta_o.asin();
+        let theta_i = clamp(-theta_o + self.shifts[lobe] + self.widths[lobe] * Self::gaussian(&mut rng), -PI / 2.0, PI / 2.0);
+        let p = lobe as Float;
+        let phi_i = 2.0 * p * gamma_t - 2.0 * gamma_o + p * PI + self.widths[lobe] * Self::gaussian(&mut rng);
+
+        let direction = tangent * theta_i.sin() + (y * phi_i.cos() + z * phi_i.sin()) * theta_i.cos();
+
+        Some(ScatteredRay::new(
+            Ray::new(record.point, direction, ray.time),
+            lobes[lobe] * (total / weights[lobe])
+        ))
+    }
+}
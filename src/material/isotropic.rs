@@ -3,6 +3,8 @@ use super::textures::Texture;
 use super::hittable::HitRecord;
 use super::material::Material;
 use super::utils::vec3::Vec3;
+use super::utils::consts::PI;
+use super::utils::Float;
 
 use std::rc::Rc;
 
@@ -25,4 +27,8 @@ impl Material for Isotropic {
             self.albedo.color(record.u, record.v, &record.point)
         ))
     }
+
+    fn scattering_pdf(&self, _ray: &Ray, _record: &HitRecord, _scattered: &Ray) -> Float {
+        1.0 / (4.0 * PI)
+    }
 }
\ No newline at end of file
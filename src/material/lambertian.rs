@@ -3,6 +3,8 @@ use super::textures::Texture;
 use super::hittable::HitRecord;
 use super::material::Material;
 use super::utils::vec3::Vec3;
+use super::utils::consts::PI;
+use super::utils::Float;
 
 use std::rc::Rc;
 
@@ -21,13 +23,25 @@ impl Lambertian {
 impl Material for Lambertian {
     fn scatter(&self, ray: &Ray, record: &HitRecord) -> Option<ScatteredRay> {
         let scatter_direction = record.normal + Vec3::random_unit();
+
+        //a shading normal can tilt the ray through the actual surface
+        if Vec3::dot_product(scatter_direction, record.geometric_normal) <= 0.0 {
+            return None;
+        }
+
+        let albedo = self.albedo.color_with_normal(record.u, record.v, &record.point, &record.normal);
         Some(ScatteredRay::new(
             Ray::new(
                 record.point, 
                 scatter_direction,
                 ray.time
             ),
-            self.albedo.color(record.u, record.v, &record.point)
+            record.color.map_or(albedo, |color| albedo * color)
         ))
     }
+
+    fn scattering_pdf(&self, _ray: &Ray, record: &HitRecord, scattered: &Ray) -> Float {
+        let cosine = Vec3::dot_product(record.normal, scattered.direction.unit_vector());
+        if cosine > 0.0 && Vec3::dot_product(scattered.direction, record.geometric_normal) > 0.0 { cosine / PI } else { 0.0 }
+    }
 }
\ No newline at end of file
@@ -2,6 +2,7 @@ use super::utils::color::Color;
 use super::material::Material;
 use super::textures::Texture;
 use super::utils::vec3::Vec3;
+use super::utils::Float;
 
 use std::rc::Rc;
 
@@ -18,7 +19,7 @@ impl Light {
 }
 
 impl Material for Light {
-    fn emit(&self, u: f64, v: f64, point: &Vec3) -> Color {
+    fn emit(&self, u: Float, v: Float, point: &Vec3) -> Color {
         self.emitted.color(u, v, point)
     }
 }
\ No newline at end of file
@@ -2,13 +2,19 @@ use super::utils::ray::{Ray, ScatteredRay};
 use super::hittable::HitRecord;
 use super::utils::color::Color;
 use super::utils::vec3::Vec3;
+use super::utils::Float;
 
 pub trait Material {
     fn scatter(&self, _ray: &Ray, _record: &HitRecord) -> Option<ScatteredRay> {
         None
     }
 
-    fn emit(&self, _u: f64, _x: f64, _point: &Vec3) -> Color {
+    //density of scatter's directions, 0 for materials whose rays can't be swapped for one towards a light
+    fn scattering_pdf(&self, _ray: &Ray, _record: &HitRecord, _scattered: &Ray) -> Float {
+        0.0
+    }
+
+    fn emit(&self, _u: Float, _x: Float, _point: &Vec3) -> Color {
         Color::default()
     }
 }
\ No newline at end of file
@@ -3,14 +3,15 @@ use super::hittable::HitRecord;
 use super::utils::color::Color;
 use super::material::Material;
 use super::utils::vec3::Vec3;
+use super::utils::Float;
 
 pub struct Metal {
     albedo: Color,
-    fuzziness: f64
+    fuzziness: Float
 }
 
 impl Metal {
-    pub fn new(albedo: Color, fuzziness: f64) -> Self {
+    pub fn new(albedo: Color, fuzziness: Float) -> Self {
         Self {
             albedo,
             fuzziness
@@ -20,11 +21,17 @@ impl Metal {
 
 impl Material for Metal {
     fn scatter(&self, ray: &Ray, record: &HitRecord) -> Option<ScatteredRay> {
-        let reflected = Vec3::reflect(ray.direction.unit_vector(), record.normal);
+        let reflected = Vec3::reflect(ray.direction.unit_vector(), record.normal) + Vec3::random_in_unit_sphere() * self.fuzziness;
+
+        //absorbed when the fuzz or a shading normal sends it through the actual surface
+        if Vec3::dot_product(reflected, record.geometric_normal) <= 0.0 {
+            return None;
+        }
+
         Some(ScatteredRay::new(
             Ray::new(
                 record.point,
-                reflected + Vec3::random_in_unit_sphere() * self.fuzziness,
+                reflected,
                 ray.time
             ),
             self.albedo,
@@ -1,9 +1,12 @@
 pub mod light;
 pub mod metal;
+#[allow(clippy::module_inception)]
 pub mod material;
 pub mod lambertian;
 pub mod dielectric;
 pub mod isotropic;
+pub mod hair;
+pub mod normal_map;
 
 use super::utils;
 use super::hittable;
@@ -0,0 +1,129 @@
+use super::utils::ray::{Ray, ScatteredRay};
+use super::textures::Texture;
+use super::hittable::HitRecord;
+use super::utils::color::Color;
+use super::material::Material;
+use super::utils::vec3::Vec3;
+use super::utils::{gamma, Float};
+
+use std::rc::Rc;
+
+const DELTA: Float = 0.0005;
+
+pub enum SurfaceMap {
+    //height texture, its average channel is the height, scaled by the factor
+    Bump(Rc<dyn Texture>, Float),
+    //tangent space normals encoded as colors in [0, 1]
+    Normal(Rc<dyn Texture>)
+}
+
+pub struct NormalMapped {
+    material: Rc<dyn Material>,
+    map: Option<SurfaceMap>
+}
+
+impl NormalMapped {
+    pub fn new(material: Rc<dyn Material>, map: Option<SurfaceMap>) -> Self {
+        Self {
+            material,
+            map
+        }
+    }
+
+    pub fn bump(material: Rc<dyn Material>, height: Rc<dyn Texture>, scale: Float) -> Self {
+        Self::new(material, Some(SurfaceMap::Bump(height, scale)))
+    }
+
+    pub fn normal(material: Rc<dyn Material>, normals: Rc<dyn Texture>) -> Self {
+        Self::new(material, Some(SurfaceMap::Normal(normals)))
+    }
+
+    //dpdu and dpdv from the primitive when it has them, otherwise any frame around the normal
+    fn tangent_frame(record: &HitRecord, normal: Vec3) -> (Vec3, Vec3) {
+        //parallel up to rounding counts as parallel, relative to the lengths so it holds in either precision
+        let dpdu = if Vec3::cross_product(record.dpdu, normal).squared_length() > gamma(3) * gamma(3) * record.dpdu.squared_length() {
+            record.dpdu
+        }
+        else {
+            let helper = if normal.x.abs() > 0.9 { Vec3::new(0.0, 1.0, 0.0) } else { Vec3::new(1.0, 0.0, 0.0) };
+            Vec3::cross_product(helper, normal).unit_vector()
+        };
+
+        let dpdv = if Vec3::cross_product(dpdu, record.dpdv).squared_length() > gamma(3) * gamma(3) * dpdu.squared_length() * record.dpdv.squared_length() {
+            record.dpdv
+        }
+        else {
+            Vec3::cross_product(normal, dpdu)
+        };
+
+        (dpdu, dpdv)
+    }
+
+    fn height(texture: &Rc<dyn Texture>, u: Float, v: Float, point: &Vec3) -> Float {
+        let color = texture.color(u, v, point);
+        (color.r + color.g + color.b) / 3.0
+    }
+
+    pub fn shading_normal(&self, record: &HitRecord) -> Vec3 {
+        let map = match &self.map {
+            Some(map) => map,
+            None => return record.normal
+        };
+
+        //the maps push along the outward normal so back faces see the same relief
+        let outward = if record.front_face { record.normal } else { -record.normal };
+        let (dpdu, dpdv) = Self::tangent_frame(record, outward);
+
+        let perturbed = match map {
+            SurfaceMap::Bump(texture, scale) => {
+                let height = Self::height(texture, record.u, record.v, &record.point);
+                let height_u = Self::height(texture, record.u + DELTA, record.v, &(record.point + dpdu * DELTA));
+                let height_v = Self::height(texture, record.u, record.v + DELTA, &(record.point + dpdv * DELTA));
+
+                let displaced_u = dpdu + outward * (scale * (height_u - height) / DELTA);
+                let displaced_v = dpdv + outward * (scale * (height_v - height) / DELTA);
+                let normal = Vec3::cross_product(displaced_u, displaced_v);
+
+                if Vec3::dot_product(normal, outward) < 0.0 { -normal } else { normal }
+            },
+            SurfaceMap::Normal(texture) => {
+                let color = texture.color(record.u, record.v, &record.point);
+                let tangent = (dpdu - outward * Vec3::dot_product(outward, dpdu)).unit_vector();
+                let mut bitangent = Vec3::cross_product(outward, tangent);
+
+                if Vec3::dot_product(bitangent, dpdv) < 0.0 {
+                    bitangent = -bitangent;
+                }
+
+                tangent * (2.0 * color.r - 1.0) + bitangent * (2.0 * color.g - 1.0) + outward * (2.0 * color.b - 1.0)
+            }
+        };
+
+        if perturbed.squared_length() <= gamma(3) * gamma(3) {
+            return record.normal;
+        }
+
+        let perturbed = perturbed.unit_vector();
+        if record.front_face { perturbed } else { -perturbed }
+    }
+}
+
+impl Material for NormalMapped {
+    fn scatter(&self, ray: &Ray, record: &HitRecord) -> Option<ScatteredRay> {
+        let mut shaded = record.clone();
+        shaded.normal = self.shading_normal(record);
+
+        self.material.scatter(ray, &shaded)
+    }
+
+    fn scattering_pdf(&self, ray: &Ray, record: &HitRecord, scattered: &Ray) -> Float {
+        let mut shaded = record.clone();
+        shaded.normal = self.shading_normal(record);
+
+        self.material.scattering_pdf(ray, &shaded, scattered)
+    }
+
+    fn emit(&self, u: Float, v: Float, point: &Vec3) -> Color {
+        self.material.emit(u, v, point)
+    }
+}
@@ -0,0 +1,146 @@
+use super::hittable::{HitRecord, Hittable};
+use super::material::material::Material;
+use super::mesh::{MeshData, TriangleMesh};
+use super::bounding::BoundingBox;
+use super::max_float;
+use super::vec3::Vec3;
+use super::ray::Ray;
+use super::{gamma, Float};
+
+use std::rc::Rc;
+
+use num::clamp;
+
+const MAX_RESOLUTION: usize = 64;
+
+fn bernstein(t: Float) -> [Float; 4] {
+    let s = 1.0 - t;
+    [s * s * s, 3.0 * t * s * s, 3.0 * t * t * s, t * t * t]
+}
+
+fn bernstein_derivative(t: Float) -> [Float; 4] {
+    let s = 1.0 - t;
+    [-3.0 * s * s, 3.0 * s * s - 6.0 * t * s, 6.0 * t * s - 3.0 * t * t, 3.0 * t * t]
+}
+
+pub struct BezierSurface {
+    patches: Vec<[Vec3; 16]>,
+    mesh: TriangleMesh
+}
+
+impl BezierSurface {
+    //control points are stored row by row, u runs along a row and v across rows
+    pub fn new(patches: Vec<[Vec3; 16]>, tolerance: Float, material: Rc<dyn Material>) -> Self {
+        let mut positions = vec![];
+        let mut normals = vec![];
+        let mut uvs = vec![];
+        let mut indices = vec![];
+
+        for patch in patches.iter() {
+            let (columns, rows) = Self::resolution(patch, tolerance);
+            let base = positions.len();
+
+            for j in 0..=rows {
+                for i in 0..=columns {
+                    let u = i as Float / columns as Float;
+                    let v = j as Float / rows as Float;
+                    let (point, normal) = Self::evaluate(patch, u, v);
+
+                    positions.push(point);
+                    normals.push(normal);
+                    uvs.push((u, v));
+                }
+            }
+
+            for j in 0..rows {
+                for i in 0..columns {
+                    let a = base + j * (columns + 1) + i;
+                    let b = a + 1;
+                    let c = a + columns + 1;
+                    let d = c + 1;
+
+                    indices.push([a, b, d]);
+                    indices.push([a, d, c]);
+                }
+            }
+        }
+
+        Self {
+            patches,
+            mesh: TriangleMesh::new(MeshData::new(positions, Some(normals), Some(uvs), indices, material))
+        }
+    }
+
+    pub fn patches(&self) -> &[[Vec3; 16]] {
+        &self.patches
+    }
+
+    pub fn evaluate(patch: &[Vec3; 16], u: Float, v: Float) -> (Vec3, Vec3) {
+        let (point, du, dv) = Self::derivatives(patch, u, v);
+        let mut normal = Vec3::cross_product(du, dv);
+
+        //degenerate corners (e.g. the top of a lid) have no tangent plane, look just inside the patch
+        let cutoff = gamma(3) * du.length() * dv.length();
+        if normal.squared_length() <= cutoff * cutoff {
+            let (_, du, dv) = Self::derivatives(patch, 0.5 + (u - 0.5) * 0.999, 0.5 + (v - 0.5) * 0.999);
+            normal = Vec3::cross_product(du, dv);
+        }
+
+        let normal = if normal.squared_length() > 0.0 { normal.unit_vector() } else { Vec3::new(0.0, 1.0, 0.0) };
+        (point, normal)
+    }
+
+    fn derivatives(patch: &[Vec3; 16], u: Float, v: Float) -> (Vec3, Vec3, Vec3) {
+        let (bu, bv) = (bernstein(u), bernstein(v));
+        let (dbu, dbv) = (bernstein_derivative(u), bernstein_derivative(v));
+
+        let mut point = Vec3::default();
+        let mut du = Vec3::default();
+        let mut dv = Vec3::default();
+
+        for (idx, control) in patch.iter().enumerate() {
+            let (row, column) = (idx / 4, idx % 4);
+
+            point = point + *control * (bv[row] * bu[column]);
+            du = du + *control * (bv[row] * dbu[column]);
+            dv = dv + *control * (dbv[row] * bu[column]);
+        }
+
+        (point, du, dv)
+    }
+
+    //flatness bound of a cubic: the polyline of N segments is within 3/4 * max|second difference| / N^2,
+    //the tolerance is split between the two directions
+    fn resolution(patch: &[Vec3; 16], tolerance: Float) -> (usize, usize) {
+        let mut along_u: Float = 0.0;
+        let mut along_v: Float = 0.0;
+
+        for a in 0..4 {
+            for b in 0..2 {
+                let row = |k: usize| patch[a * 4 + b + k];
+                let column = |k: usize| patch[(b + k) * 4 + a];
+
+                along_u = max_float(along_u, (row(0) - row(1) * 2.0 + row(2)).length());
+                along_v = max_float(along_v, (column(0) - column(1) * 2.0 + column(2)).length());
+            }
+        }
+
+        let segments = |curvature: Float| clamp((1.5 * curvature / tolerance).sqrt().ceil() as usize, 1, MAX_RESOLUTION);
+
+        (segments(along_u), segments(along_v))
+    }
+}
+
+impl Hittable for BezierSurface {
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
+        self.mesh.hit(ray, tmin, tmax)
+    }
+
+    fn occluded(&self, ray: &Ray, tmin: Float, tmax: Float) -> bool {
+        self.mesh.occluded(ray, tmin, tmax)
+    }
+
+    fn bounding_box(&self, t0: Float, t1: Float) -> Option<BoundingBox> {
+        self.mesh.bounding_box(t0, t1)
+    }
+}
@@ -0,0 +1,110 @@
+use super::hittable::{HitRecord, Hittable, HittableList, FlipFace};
+use super::material::material::Material;
+use super::bounding::BoundingBox;
+use super::disk::Disk;
+use super::vec3::Vec3;
+use super::ray::Ray;
+use super::{gamma, Float};
+use super::consts::PI;
+
+use std::rc::Rc;
+
+pub struct Cone {
+    center: Vec3,
+    radius: Float,
+    height: Float,
+    caps: HittableList,
+    material: Rc<dyn Material>
+}
+
+impl Cone {
+    pub fn new(center: Vec3, radius: Float, height: Float, capped: bool, material: Rc<dyn Material>) -> Self {
+        let mut caps = HittableList::new();
+
+        if capped {
+            caps.push(Rc::new(FlipFace::new(Rc::new(Disk::new(
+                center, radius,
+                material.clone()
+            )))));
+        }
+
+        Self {
+            center,
+            radius,
+            height,
+            caps,
+            material
+        }
+    }
+
+    fn get_hit_record(&self, ray: &Ray, time: Float) -> HitRecord {
+        let point = ray.at(time);
+        let local = point - self.center;
+        let slope = self.radius / self.height;
+        let phi = local.z.atan2(local.x);
+
+        HitRecord::new(
+            ray,
+            point,
+            Vec3::new(local.x, slope * slope * (self.height - local.y), local.z).unit_vector(),
+            time,
+            1.0 - (phi + PI) / (2.0 * PI),
+            local.y / self.height,
+            self.material.clone()
+        )
+    }
+
+    fn hit_side(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
+        let oc = ray.origin - self.center;
+        let direction = ray.direction;
+        let slope = self.radius / self.height;
+        let k = slope * slope;
+        let apex = self.height - oc.y;
+
+        let a = direction.x * direction.x + direction.z * direction.z - k * direction.y * direction.y;
+        let b = oc.x * direction.x + oc.z * direction.z + k * apex * direction.y;
+        let c = oc.x * oc.x + oc.z * oc.z - k * apex * apex;
+
+        //a is zero up to rounding when the ray runs parallel to the surface, then only the linear term is left
+        let mut times = if a.abs() <= gamma(3) * (direction.x * direction.x + direction.z * direction.z + k * direction.y * direction.y) {
+            if b == 0.0 { vec![] } else { vec![-c / (2.0 * b)] }
+        }
+        else {
+            let discriminant = b * b - a * c;
+
+            if discriminant <= 0.0 {
+                return None;
+            }
+
+            let root = discriminant.sqrt();
+            vec![(-b - root) / a, (-b + root) / a]
+        };
+        times.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        for time in times {
+            let y = oc.y + direction.y * time;
+
+            if time < tmax && time > tmin && y >= 0.0 && y <= self.height {
+                return Some(self.get_hit_record(ray, time));
+            }
+        }
+
+        None
+    }
+}
+
+impl Hittable for Cone {
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
+        match self.hit_side(ray, tmin, tmax) {
+            Some(record) => self.caps.hit(ray, tmin, record.time).or(Some(record)),
+            None => self.caps.hit(ray, tmin, tmax)
+        }
+    }
+
+    fn bounding_box(&self, _t0: Float, _t1: Float) -> Option<BoundingBox> {
+        Some(BoundingBox::new(
+            self.center - Vec3::new(self.radius, 0.0, self.radius),
+            self.center + Vec3::new(self.radius, self.height, self.radius)
+        ))
+    }
+}
@@ -1,24 +1,24 @@
 use super::material::{isotropic::Isotropic, material::Material};
 use super::hittable::{HitRecord, Hittable};
 use super::bounding::BoundingBox;
-use super::{min_f64, max_f64};
+use super::{min_float, max_float};
 use super::textures::Texture;
 use super::vec3::Vec3;
 use super::ray::Ray;
+use super::Float;
 
 use std::rc::Rc;
-use std::f64;
 
 use rand::Rng;
 
 pub struct ConstantMedium {
     object: Rc<dyn Hittable>,
     material: Rc<dyn Material>,
-    density: f64,
+    density: Float,
 }
 
 impl ConstantMedium {
-    pub fn new(object: Rc<dyn Hittable>, texture: Rc<dyn Texture>, density: f64) -> Self {
+    pub fn new(object: Rc<dyn Hittable>, texture: Rc<dyn Texture>, density: Float) -> Self {
         Self {
             object,
             density: -1.0/density,
@@ -28,35 +28,23 @@ impl ConstantMedium {
 }
 
 impl Hittable for ConstantMedium {
-    fn hit(&self, ray: &Ray, tmin: f64, tmax: f64) -> Option<HitRecord> {
-        let mut record1 = if let Some(record) = self.object.hit(ray, f64::MIN, f64::MAX) {
-            record
-        }
-        else{
-            return None;
-        };
-
-        
-        let mut record2 = if let Some(record) = self.object.hit(ray, record1.time + 0.0001, f64::MAX) {
-            record
-        }
-        else{
-            return None;
-        };
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
+        let mut record1 = self.object.hit(ray, Float::MIN, Float::MAX)?;
+        let mut record2 = self.object.hit(ray, record1.time + 0.0001, Float::MAX)?;
         
-        record1.time = max_f64(tmin, record1.time);
-        record2.time = min_f64(tmax, record2.time);
+        record1.time = max_float(tmin, record1.time);
+        record2.time = min_float(tmax, record2.time);
         
         if record1.time >= record2.time {
             return None;
         }
 
-        record1.time = max_f64(0.0, record1.time);
+        record1.time = max_float(0.0, record1.time);
 
         let mut rng = rand::thread_rng();
         let ray_length = ray.direction.length();
         let distance_inside = (record2.time - record1.time) * ray_length;
-        let hit_distance = self.density * (rng.gen_range(0.0, 1.0) as f64).ln();
+        let hit_distance = self.density * (rng.gen_range(0.0, 1.0) as Float).ln();
 
         if hit_distance > distance_inside {
             return None;
@@ -64,7 +52,7 @@ impl Hittable for ConstantMedium {
 
         let time = record1.time + hit_distance / ray_length;
         Some(HitRecord::new(
-            &ray,
+            ray,
             ray.at(time),
             Vec3::new(1.0, 0.0, 0.0),  //doesn't matter
             time,
@@ -74,7 +62,7 @@ impl Hittable for ConstantMedium {
         ))
     }
 
-    fn bounding_box(&self, t0: f64, t1: f64) -> Option<BoundingBox> {
+    fn bounding_box(&self, t0: Float, t1: Float) -> Option<BoundingBox> {
         self.object.bounding_box(t0, t1)
     }
 }
\ No newline at end of file
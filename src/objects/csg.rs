@@ -0,0 +1,112 @@
+use super::hittable::{HitRecord, Hittable};
+use super::bounding::BoundingBox;
+use super::{min_float, max_float};
+use super::vec3::Vec3;
+use super::ray::Ray;
+use super::Float;
+
+use std::rc::Rc;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CsgOperation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl CsgOperation {
+    fn inside(&self, left: bool, right: bool) -> bool {
+        match self {
+            CsgOperation::Union => left || right,
+            CsgOperation::Intersection => left && right,
+            CsgOperation::Difference => left && !right,
+        }
+    }
+}
+
+pub struct Csg {
+    left: Rc<dyn Hittable>,
+    right: Rc<dyn Hittable>,
+    operation: CsgOperation,
+}
+
+impl Csg {
+    pub fn new(left: Rc<dyn Hittable>, right: Rc<dyn Hittable>, operation: CsgOperation) -> Self {
+        Self {
+            left,
+            right,
+            operation
+        }
+    }
+
+    pub fn union(left: Rc<dyn Hittable>, right: Rc<dyn Hittable>) -> Self {
+        Self::new(left, right, CsgOperation::Union)
+    }
+
+    pub fn intersection(left: Rc<dyn Hittable>, right: Rc<dyn Hittable>) -> Self {
+        Self::new(left, right, CsgOperation::Intersection)
+    }
+
+    pub fn difference(left: Rc<dyn Hittable>, right: Rc<dyn Hittable>) -> Self {
+        Self::new(left, right, CsgOperation::Difference)
+    }
+}
+
+impl Hittable for Csg {
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
+        self.hit_all(ray, tmin, tmax).into_iter().next()
+    }
+
+    fn hit_all(&self, ray: &Ray, tmin: Float, tmax: Float) -> Vec<HitRecord> {
+        let mut events: Vec<(HitRecord, bool)> = self.left.hit_all(ray, Float::MIN, Float::MAX)
+            .into_iter()
+            .map(|record| (record, true))
+            .chain(self.right.hit_all(ray, Float::MIN, Float::MAX).into_iter().map(|record| (record, false)))
+            .collect();
+
+        events.sort_by(|a, b| a.0.time.total_cmp(&b.0.time));
+
+        let mut in_left = false;
+        let mut in_right = false;
+        let mut inside = false;
+        let mut result = vec![];
+
+        for (mut record, is_left) in events {
+            if is_left {
+                in_left = record.front_face;
+            }
+            else {
+                in_right = record.front_face;
+            }
+
+            let now_inside = self.operation.inside(in_left, in_right);
+
+            if now_inside != inside {
+                inside = now_inside;
+
+                if record.time > tmin && record.time < tmax {
+                    record.front_face = now_inside;
+                    result.push(record);
+                }
+            }
+        }
+
+        result
+    }
+
+    fn bounding_box(&self, t0: Float, t1: Float) -> Option<BoundingBox> {
+        let left = self.left.bounding_box(t0, t1);
+        let right = self.right.bounding_box(t0, t1);
+
+        match (self.operation, left, right) {
+            (CsgOperation::Union, Some(a), Some(b)) => Some(BoundingBox::surrounding_box(&a, &b)),
+            (CsgOperation::Union, _, _) => None,
+            (CsgOperation::Intersection, Some(a), Some(b)) => Some(BoundingBox::new(
+                Vec3::new(max_float(a.min.x, b.min.x), max_float(a.min.y, b.min.y), max_float(a.min.z, b.min.z)),
+                Vec3::new(min_float(a.max.x, b.max.x), min_float(a.max.y, b.max.y), min_float(a.max.z, b.max.z))
+            )),
+            (CsgOperation::Intersection, a, b) => a.or(b),
+            (CsgOperation::Difference, a, _) => a,
+        }
+    }
+}
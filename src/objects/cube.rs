@@ -4,6 +4,7 @@ use super::bounding::BoundingBox;
 use super::rectangles::*;
 use super::vec3::Vec3;
 use super::ray::Ray;
+use super::Float;
 
 use std::rc::Rc;
 
@@ -11,6 +12,7 @@ pub struct Cube {
     top_right: Vec3,
     bottom_left: Vec3,
     sides: HittableList,
+    material: Rc<dyn Material>,
 }
 
 impl Cube {
@@ -57,16 +59,73 @@ impl Cube {
             sides,
             bottom_left,
             top_right,
+            material,
         }
     }
+
+    //the same record the side would give, the sides facing the negative axes are flipped like in from_vertices
+    fn get_hit_record(&self, ray: &Ray, time: Float, axis: usize, outward: Float) -> HitRecord {
+        let (a, b) = match axis {
+            0 => (1, 2),
+            1 => (0, 2),
+            _ => (0, 1)
+        };
+
+        let mut min = self.bottom_left;
+        let mut max = self.top_right;
+        if outward > 0.0 { min[axis] = max[axis] } else { max[axis] = min[axis] }
+
+        let mut record = rectangle_record(ray, time, a, b, min, max, self.material.clone());
+
+        if outward < 0.0 {
+            record.front_face = !record.front_face;
+        }
+
+        record
+    }
 }
 
 impl Hittable for Cube {
-    fn hit(&self, ray: &Ray, tmin: f64, tmax: f64) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
         self.sides.hit(ray, tmin, tmax)
     }
 
-    fn bounding_box(&self, _t0: f64, _t1: f64) -> Option<BoundingBox> {
+    fn hit_all(&self, ray: &Ray, tmin: Float, tmax: Float) -> Vec<HitRecord> {
+        let mut near = (Float::MIN, 0);
+        let mut far = (Float::MAX, 0);
+
+        for idx in 0..3 {
+            let inverse_direction = 1.0 / ray.direction[idx];
+
+            let mut t0 = (self.bottom_left[idx] - ray.origin[idx]) * inverse_direction;
+            let mut t1 = (self.top_right[idx] - ray.origin[idx]) * inverse_direction;
+
+            if inverse_direction < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+
+            if t0 > near.0 {
+                near = (t0, idx);
+            }
+            if t1 < far.0 {
+                far = (t1, idx);
+            }
+        }
+
+        if far.0 <= near.0 {
+            return vec![];
+        }
+
+        let near_outward = -ray.direction[near.1].signum();
+        let far_outward = ray.direction[far.1].signum();
+
+        [(near, near_outward), (far, far_outward)].iter()
+            .filter(|((time, _), _)| *time < tmax && *time > tmin)
+            .map(|((time, axis), outward)| self.get_hit_record(ray, *time, *axis, *outward))
+            .collect()
+    }
+
+    fn bounding_box(&self, _t0: Float, _t1: Float) -> Option<BoundingBox> {
         Some(BoundingBox::new(
             self.bottom_left,
             self.top_right
@@ -0,0 +1,284 @@
+use super::hittable::{HitRecord, Hittable};
+use super::material::material::Material;
+use super::bounding::{BoundingBox, BvhNode};
+use super::max_float;
+use super::vec3::Vec3;
+use super::ray::Ray;
+use super::Float;
+
+use std::rc::Rc;
+
+use num::clamp;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CurveBasis {
+    Bezier,
+    BSpline,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CurveShape {
+    Flat,
+    Cylinder,
+}
+
+fn evaluate(cp: &[Vec3; 4], t: Float) -> Vec3 {
+    let s = 1.0 - t;
+    cp[0] * (s * s * s) + cp[1] * (3.0 * t * s * s) + cp[2] * (3.0 * t * t * s) + cp[3] * (t * t * t)
+}
+
+fn derivative(cp: &[Vec3; 4], t: Float) -> Vec3 {
+    let s = 1.0 - t;
+    (cp[1] - cp[0]) * (3.0 * s * s) + (cp[2] - cp[1]) * (6.0 * t * s) + (cp[3] - cp[2]) * (3.0 * t * t)
+}
+
+fn split(cp: &[Vec3; 4]) -> ([Vec3; 4], [Vec3; 4]) {
+    let ab = (cp[0] + cp[1]) / 2.0;
+    let bc = (cp[1] + cp[2]) / 2.0;
+    let cd = (cp[2] + cp[3]) / 2.0;
+    let abc = (ab + bc) / 2.0;
+    let bcd = (bc + cd) / 2.0;
+    let middle = (abc + bcd) / 2.0;
+
+    ([cp[0], ab, abc, middle], [middle, bcd, cd, cp[3]])
+}
+
+pub struct CurveSegment {
+    control: [Vec3; 4],
+    widths: (Float, Float),
+    u_range: (Float, Float),
+    shape: CurveShape,
+    material: Rc<dyn Material>
+}
+
+impl CurveSegment {
+    pub fn new(control: [Vec3; 4], widths: (Float, Float), u_range: (Float, Float), shape: CurveShape, material: Rc<dyn Material>) -> Self {
+        Self {
+            control,
+            widths,
+            u_range,
+            shape,
+            material
+        }
+    }
+
+    fn width(&self, u: Float) -> Float {
+        self.widths.0 * (1.0 - u) + self.widths.1 * u
+    }
+
+    //cp is in ray space: the ray starts at the origin and runs along +z
+    fn recursive_hit(&self, cp: &[Vec3; 4], u0: Float, u1: Float, depth: usize, zmin: Float, zmax: &mut Float) -> Option<(Float, Float, Vec3, Float)> {
+        let half_width = max_float(self.width(u0), self.width(u1)) / 2.0;
+        let bounds = BoundingBox::from_points(cp);
+
+        if bounds.min.x - half_width > 0.0 || bounds.max.x + half_width < 0.0
+            || bounds.min.y - half_width > 0.0 || bounds.max.y + half_width < 0.0
+            || bounds.max.z + half_width < zmin || bounds.min.z - half_width > *zmax {
+            return None;
+        }
+
+        if depth > 0 {
+            let (left, right) = split(cp);
+            let middle = (u0 + u1) / 2.0;
+
+            let near = self.recursive_hit(&left, u0, middle, depth - 1, zmin, zmax);
+            let far = self.recursive_hit(&right, middle, u1, depth - 1, zmin, zmax);
+
+            return far.or(near);
+        }
+
+        let start_edge = (cp[1].y - cp[0].y) * -cp[0].y + cp[0].x * (cp[0].x - cp[1].x);
+        let end_edge = (cp[2].y - cp[3].y) * -cp[3].y + cp[3].x * (cp[3].x - cp[2].x);
+
+        if start_edge < 0.0 || end_edge < 0.0 {
+            return None;
+        }
+
+        let segment = cp[3] - cp[0];
+        let denominator = segment.x * segment.x + segment.y * segment.y;
+
+        if denominator == 0.0 {
+            return None;
+        }
+
+        let w = clamp(-(cp[0].x * segment.x + cp[0].y * segment.y) / denominator, 0.0, 1.0);
+        let u = u0 + (u1 - u0) * w;
+        let radius = self.width(u) / 2.0;
+
+        let point = evaluate(cp, w);
+        let distance = (point.x * point.x + point.y * point.y).sqrt();
+
+        if distance > radius {
+            return None;
+        }
+
+        let z = match self.shape {
+            CurveShape::Flat => point.z,
+            CurveShape::Cylinder => point.z - (radius * radius - distance * distance).sqrt(),
+        };
+
+        if z < zmin || z > *zmax {
+            return None;
+        }
+
+        let tangent = derivative(cp, w);
+        let side = tangent.x * -point.y + point.x * tangent.y;
+        let h = if side > 0.0 { distance / radius } else { -distance / radius };
+
+        *zmax = z;
+        Some((z, u, Vec3::new(-point.x, -point.y, 0.0), h))
+    }
+}
+
+impl Hittable for CurveSegment {
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
+        let length = ray.direction.length();
+        let dz = ray.direction / length;
+        let helper = if dz.x.abs() > 0.9 { Vec3::new(0.0, 1.0, 0.0) } else { Vec3::new(1.0, 0.0, 0.0) };
+        let dx = Vec3::cross_product(helper, dz).unit_vector();
+        let dy = Vec3::cross_product(dz, dx);
+
+        let to_ray = |point: Vec3| {
+            let q = point - ray.origin;
+            Vec3::new(Vec3::dot_product(q, dx), Vec3::dot_product(q, dy), Vec3::dot_product(q, dz))
+        };
+
+        let cp = [to_ray(self.control[0]), to_ray(self.control[1]), to_ray(self.control[2]), to_ray(self.control[3])];
+
+        let mut curvature: Float = 0.0;
+        for idx in 0..2 {
+            let second = cp[idx] - cp[idx + 1] * 2.0 + cp[idx + 2];
+            curvature = max_float(curvature, max_float(second.x.abs(), max_float(second.y.abs(), second.z.abs())));
+        }
+
+        let epsilon = max_float(self.widths.0, self.widths.1) * 0.05;
+        let depth = if curvature > 0.0 {
+            clamp(((Float::sqrt(2.0) * 6.0 * curvature / (8.0 * epsilon)).log2() / 2.0).ceil(), 0.0, 10.0) as usize
+        }
+        else {
+            0
+        };
+
+        let mut zmax = tmax * length;
+        let (z, u, offset, h) = self.recursive_hit(&cp, 0.0, 1.0, depth, tmin * length, &mut zmax)?;
+
+        let time = z / length;
+        let tangent = derivative(&self.control, u).unit_vector();
+        let facing = (-dz - tangent * Vec3::dot_product(-dz, tangent)).unit_vector();
+
+        let normal = match self.shape {
+            CurveShape::Flat => facing,
+            CurveShape::Cylinder => {
+                let side = dx * offset.x + dy * offset.y;
+
+                if side.squared_length() > 0.0 {
+                    let side = (side - tangent * Vec3::dot_product(side, tangent)).unit_vector();
+                    facing * (1.0 - h * h).max(0.0).sqrt() + side * h.abs()
+                }
+                else {
+                    facing
+                }
+            }
+        };
+
+        let mut record = HitRecord::new(
+            ray,
+            ray.at(time),
+            normal,
+            time,
+            self.u_range.0 + (self.u_range.1 - self.u_range.0) * u,
+            (h + 1.0) / 2.0,
+            self.material.clone()
+        );
+        record.dpdu = tangent;
+
+        Some(record)
+    }
+
+    fn bounding_box(&self, _t0: Float, _t1: Float) -> Option<BoundingBox> {
+        let obj_box = BoundingBox::from_points(&self.control);
+        let half_width = max_float(self.widths.0, self.widths.1) / 2.0;
+        let padding = Vec3::new(half_width, half_width, half_width);
+
+        Some(BoundingBox::new(obj_box.min - padding, obj_box.max + padding))
+    }
+}
+
+pub struct Curves {
+    bvh: BvhNode
+}
+
+impl Curves {
+    //a Bezier curve uses 3n + 1 points, a B-spline any number >= 4; widths are given per point
+    pub fn new(points: &[Vec3], widths: &[Float], basis: CurveBasis, shape: CurveShape, material: Rc<dyn Material>) -> Self {
+        if points.len() != widths.len() {
+            panic!("Every curve point needs a width");
+        }
+
+        match basis {
+            CurveBasis::Bezier if points.len() < 4 || !(points.len() - 1).is_multiple_of(3) => panic!("A Bezier curve needs 3n + 1 control points, with n >= 1"),
+            CurveBasis::BSpline if points.len() < 4 => panic!("A B-spline curve needs at least 4 control points"),
+            _ => {}
+        }
+
+        let mut segments: Vec<Rc<dyn Hittable>> = vec![];
+
+        match basis {
+            CurveBasis::Bezier => {
+                let count = (points.len() - 1) / 3;
+
+                for idx in 0..count {
+                    let p = &points[idx * 3..idx * 3 + 4];
+
+                    segments.push(Rc::new(CurveSegment::new(
+                        [p[0], p[1], p[2], p[3]],
+                        (widths[idx * 3], widths[idx * 3 + 3]),
+                        (idx as Float / count as Float, (idx + 1) as Float / count as Float),
+                        shape,
+                        material.clone()
+                    )));
+                }
+            },
+            CurveBasis::BSpline => {
+                let count = points.len().saturating_sub(3);
+
+                for idx in 0..count {
+                    let p = &points[idx..idx + 4];
+
+                    segments.push(Rc::new(CurveSegment::new(
+                        [
+                            (p[0] + p[1] * 4.0 + p[2]) / 6.0,
+                            (p[1] * 2.0 + p[2]) / 3.0,
+                            (p[1] + p[2] * 2.0) / 3.0,
+                            (p[1] + p[2] * 4.0 + p[3]) / 6.0
+                        ],
+                        (widths[idx + 1], widths[idx + 2]),
+                        (idx as Float / count as Float, (idx + 1) as Float / count as Float),
+                        shape,
+                        material.clone()
+                    )));
+                }
+            }
+        }
+
+        let size = segments.len();
+
+        Self {
+            bvh: BvhNode::new(&mut segments, 0, size, 0.0, 1.0)
+        }
+    }
+}
+
+impl Hittable for Curves {
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
+        self.bvh.hit(ray, tmin, tmax)
+    }
+
+    fn occluded(&self, ray: &Ray, tmin: Float, tmax: Float) -> bool {
+        self.bvh.occluded(ray, tmin, tmax)
+    }
+
+    fn bounding_box(&self, t0: Float, t1: Float) -> Option<BoundingBox> {
+        self.bvh.bounding_box(t0, t1)
+    }
+}
@@ -0,0 +1,101 @@
+use super::hittable::{HitRecord, Hittable, HittableList, FlipFace};
+use super::material::material::Material;
+use super::bounding::BoundingBox;
+use super::disk::Disk;
+use super::vec3::Vec3;
+use super::ray::Ray;
+use super::Float;
+use super::consts::PI;
+
+use std::rc::Rc;
+
+pub struct Cylinder {
+    center: Vec3,
+    radius: Float,
+    height: Float,
+    caps: HittableList,
+    material: Rc<dyn Material>
+}
+
+impl Cylinder {
+    pub fn new(center: Vec3, radius: Float, height: Float, capped: bool, material: Rc<dyn Material>) -> Self {
+        let mut caps = HittableList::new();
+
+        if capped {
+            //top
+            caps.push(Rc::new(Disk::new(
+                center + Vec3::new(0.0, height, 0.0), radius,
+                material.clone()
+            )));
+
+            //bottom
+            caps.push(Rc::new(FlipFace::new(Rc::new(Disk::new(
+                center, radius,
+                material.clone()
+            )))));
+        }
+
+        Self {
+            center,
+            radius,
+            height,
+            caps,
+            material
+        }
+    }
+
+    fn get_hit_record(&self, ray: &Ray, time: Float) -> HitRecord {
+        let point = ray.at(time);
+        let local = point - self.center;
+        let phi = local.z.atan2(local.x);
+
+        HitRecord::new(
+            ray,
+            point,
+            Vec3::new(local.x, 0.0, local.z) / self.radius,
+            time,
+            1.0 - (phi + PI) / (2.0 * PI),
+            local.y / self.height,
+            self.material.clone()
+        )
+    }
+
+    fn hit_side(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
+        let oc = ray.origin - self.center;
+        let a = ray.direction.x * ray.direction.x + ray.direction.z * ray.direction.z;
+        let b = oc.x * ray.direction.x + oc.z * ray.direction.z;
+        let c = oc.x * oc.x + oc.z * oc.z - self.radius * self.radius;
+        let discriminant = b * b - a * c;
+
+        if a == 0.0 || discriminant <= 0.0 {
+            return None;
+        }
+
+        let root = discriminant.sqrt();
+        for time in [(-b - root) / a, (-b + root) / a].iter() {
+            let y = oc.y + ray.direction.y * time;
+
+            if *time < tmax && *time > tmin && y >= 0.0 && y <= self.height {
+                return Some(self.get_hit_record(ray, *time));
+            }
+        }
+
+        None
+    }
+}
+
+impl Hittable for Cylinder {
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
+        match self.hit_side(ray, tmin, tmax) {
+            Some(record) => self.caps.hit(ray, tmin, record.time).or(Some(record)),
+            None => self.caps.hit(ray, tmin, tmax)
+        }
+    }
+
+    fn bounding_box(&self, _t0: Float, _t1: Float) -> Option<BoundingBox> {
+        Some(BoundingBox::new(
+            self.center - Vec3::new(self.radius, 0.0, self.radius),
+            self.center + Vec3::new(self.radius, self.height, self.radius)
+        ))
+    }
+}
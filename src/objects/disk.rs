@@ -0,0 +1,72 @@
+use super::hittable::{HitRecord, Hittable};
+use super::material::material::Material;
+use super::bounding::BoundingBox;
+use super::vec3::Vec3;
+use super::ray::Ray;
+use super::Float;
+use super::consts::PI;
+
+use std::rc::Rc;
+
+pub struct Disk {
+    center: Vec3,
+    radius: Float,
+    material: Rc<dyn Material>
+}
+
+impl Disk {
+    pub fn new(center: Vec3, radius: Float, material: Rc<dyn Material>) -> Self {
+        Self {
+            center,
+            radius,
+            material
+        }
+    }
+}
+
+impl Hittable for Disk {
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
+        let time = (self.center.y - ray.origin.y) / ray.direction.y;
+
+        //a ray in the plane of the disk gives nan, which no range contains
+        if !(tmin..=tmax).contains(&time) {
+            return None;
+        }
+
+        let point = ray.at(time);
+        let x = point.x - self.center.x;
+        let z = point.z - self.center.z;
+        let distance = (x * x + z * z).sqrt();
+
+        if distance > self.radius {
+            return None;
+        }
+
+        let phi = z.atan2(x);
+
+        let mut record = HitRecord::new(
+            ray,
+            point,
+            Vec3::new(0.0, 1.0, 0.0),
+            time,
+            1.0 - (phi + PI) / (2.0 * PI),
+            distance / self.radius,
+            self.material.clone()
+        );
+
+        record.dpdu = Vec3::new(z, 0.0, -x) * (2.0 * PI);
+
+        if distance > 0.0 {
+            record.dpdv = Vec3::new(x, 0.0, z) * (self.radius / distance);
+        }
+
+        Some(record)
+    }
+
+    fn bounding_box(&self, _t0: Float, _t1: Float) -> Option<BoundingBox> {
+        Some(BoundingBox::new(
+            self.center - Vec3::new(self.radius, 0.0001, self.radius),
+            self.center + Vec3::new(self.radius, 0.0001, self.radius)
+        ))
+    }
+}
@@ -0,0 +1,175 @@
+use super::hittable::{HitRecord, Hittable};
+use super::material::material::Material;
+use super::mesh::{MeshData, TriangleMesh};
+use super::bounding::BoundingBox;
+use super::textures::Texture;
+use super::vec3::Vec3;
+use super::ray::Ray;
+use super::Float;
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+const MAX_PASSES: usize = 16;
+//a pass at most quadruples the triangles, the last one that could go past this isn't started
+const MAX_TRIANGLES: usize = 1 << 22;
+
+#[derive(Clone, Copy)]
+struct Vertex {
+    position: Vec3,
+    normal: Vec3,
+    uv: (Float, Float)
+}
+
+impl Vertex {
+    fn midpoint(a: &Vertex, b: &Vertex) -> Self {
+        let normal = a.normal + b.normal;
+
+        Self {
+            position: (a.position + b.position) / 2.0,
+            normal: if normal.squared_length() > 0.0 { normal.unit_vector() } else { a.normal },
+            uv: ((a.uv.0 + b.uv.0) / 2.0, (a.uv.1 + b.uv.1) / 2.0)
+        }
+    }
+}
+
+//splits every edge longer than edge_length, the split is decided per edge so neighbours stay watertight
+fn tessellate(vertices: &mut Vec<Vertex>, mut triangles: Vec<[usize; 3]>, edge_length: Float) -> Vec<[usize; 3]> {
+    let target = edge_length * edge_length;
+
+    for _ in 0..MAX_PASSES {
+        if triangles.len() * 4 > MAX_TRIANGLES {
+            break;
+        }
+
+        let mut midpoints: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut result = Vec::with_capacity(triangles.len() * 4);
+
+        for triangle in triangles.iter() {
+            let mut split = [None; 3];
+
+            for (k, mid) in split.iter_mut().enumerate() {
+                let (a, b) = (triangle[k], triangle[(k + 1) % 3]);
+
+                if (vertices[a].position - vertices[b].position).squared_length() > target {
+                    let key = (a.min(b), a.max(b));
+
+                    *mid = Some(*midpoints.entry(key).or_insert_with(|| {
+                        vertices.push(Vertex::midpoint(&vertices[a], &vertices[b]));
+                        vertices.len() - 1
+                    }));
+                }
+            }
+
+            //rotates the triangle so the split edges come first
+            let count = split.iter().filter(|mid| mid.is_some()).count();
+            let shift = match count {
+                1 => split.iter().position(|mid| mid.is_some()).unwrap(),
+                2 => (split.iter().position(|mid| mid.is_none()).unwrap() + 1) % 3,
+                _ => 0
+            };
+
+            let [a, b, c] = [triangle[shift], triangle[(shift + 1) % 3], triangle[(shift + 2) % 3]];
+            let [ab, bc, ca] = [split[shift], split[(shift + 1) % 3], split[(shift + 2) % 3]];
+
+            match (ab, bc, ca) {
+                (Some(ab), Some(bc), Some(ca)) => {
+                    result.extend_from_slice(&[[a, ab, ca], [ab, b, bc], [ca, bc, c], [ab, bc, ca]]);
+                },
+                (Some(ab), Some(bc), None) => {
+                    result.extend_from_slice(&[[ab, b, bc], [a, ab, bc], [a, bc, c]]);
+                },
+                (Some(ab), None, None) => {
+                    result.extend_from_slice(&[[a, ab, c], [ab, b, c]]);
+                },
+                _ => result.push([a, b, c])
+            }
+        }
+
+        let done = midpoints.is_empty();
+        triangles = result;
+
+        if done {
+            break;
+        }
+    }
+
+    triangles
+}
+
+pub struct DisplacedMesh {
+    mesh: TriangleMesh
+}
+
+impl DisplacedMesh {
+    pub fn new(data: MeshData, displacement: Rc<dyn Texture>, scale: Float, edge_length: Float) -> Self {
+        if edge_length.is_nan() || edge_length <= 0.0 {
+            panic!("The edge length of a displaced mesh must be positive");
+        }
+
+        let normals = match &data.normals {
+            Some(normals) => normals.clone(),
+            None => data.smooth_normals()
+        };
+
+        let mut vertices: Vec<Vertex> = (0..data.positions.len())
+            .map(|idx| Vertex {
+                position: data.positions[idx].into(),
+                normal: normals[idx].into(),
+                uv: data.uv(idx).unwrap_or((0.0, 0.0))
+            })
+            .collect();
+
+        let indices = tessellate(&mut vertices, data.indices.clone(), edge_length);
+
+        let positions = vertices.iter()
+            .map(|vertex| {
+                let color = displacement.color(vertex.uv.0, vertex.uv.1, &vertex.position);
+                let height = (color.r + color.g + color.b) / 3.0;
+
+                vertex.position + vertex.normal * (height * scale)
+            })
+            .collect();
+
+        let uvs = data.uvs.as_ref().map(|_| vertices.iter().map(|vertex| vertex.uv).collect());
+
+        let mut displaced = MeshData::new(positions, None, uvs, indices, data.material.clone());
+        displaced.normals = Some(displaced.smooth_normals());
+
+        Self {
+            mesh: TriangleMesh::new(displaced)
+        }
+    }
+
+    pub fn from_quad(origin: Vec3, u: Vec3, v: Vec3, displacement: Rc<dyn Texture>, scale: Float, edge_length: Float, material: Rc<dyn Material>) -> Self {
+        let normal = Vec3::cross_product(u, v).unit_vector();
+
+        let data = MeshData::new(
+            vec![origin, origin + u, origin + u + v, origin + v],
+            Some(vec![normal; 4]),
+            Some(vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]),
+            vec![[0, 1, 2], [0, 2, 3]],
+            material
+        );
+
+        Self::new(data, displacement, scale, edge_length)
+    }
+
+    pub fn mesh(&self) -> &TriangleMesh {
+        &self.mesh
+    }
+}
+
+impl Hittable for DisplacedMesh {
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
+        self.mesh.hit(ray, tmin, tmax)
+    }
+
+    fn occluded(&self, ray: &Ray, tmin: Float, tmax: Float) -> bool {
+        self.mesh.occluded(ray, tmin, tmax)
+    }
+
+    fn bounding_box(&self, t0: Float, t1: Float) -> Option<BoundingBox> {
+        self.mesh.bounding_box(t0, t1)
+    }
+}
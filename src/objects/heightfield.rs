@@ -0,0 +1,213 @@
+use super::hittable::{HitRecord, Hittable};
+use super::material::material::Material;
+use super::bounding::BoundingBox;
+use super::mesh::intersect_triangle;
+use super::{min_float, max_float};
+use super::vec3::Vec3;
+use super::ray::Ray;
+use super::Float;
+
+use std::rc::Rc;
+
+use image::DynamicImage;
+
+pub struct Heightfield {
+    bottom_left: Vec3,
+    cell_width: Float,
+    cell_depth: Float,
+    columns: usize,
+    rows: usize,
+    heights: Vec<Float>,
+    normals: Vec<Vec3>,
+    bounds: Vec<(Float, Float)>,
+    obj_box: BoundingBox,
+    material: Rc<dyn Material>
+}
+
+impl Heightfield {
+    //heights are in [0, 1] and laid out row by row along x, size.y is the maximum height
+    pub fn new(heights: Vec<Float>, columns: usize, rows: usize, bottom_left: Vec3, size: Vec3, material: Rc<dyn Material>) -> Self {
+        if columns < 2 || rows < 2 || heights.len() != columns * rows {
+            panic!("A heightfield needs at least 2x2 samples");
+        }
+
+        let cell_width = size.x / (columns - 1) as Float;
+        let cell_depth = size.z / (rows - 1) as Float;
+        let heights: Vec<Float> = heights.iter().map(|h| bottom_left.y + h * size.y).collect();
+
+        let at = |i: usize, j: usize| heights[j * columns + i];
+
+        let mut normals = Vec::with_capacity(heights.len());
+        for j in 0..rows {
+            for i in 0..columns {
+                let (left, right) = (i.saturating_sub(1), (i + 1).min(columns - 1));
+                let (back, front) = (j.saturating_sub(1), (j + 1).min(rows - 1));
+
+                let dx = (at(right, j) - at(left, j)) / ((right - left) as Float * cell_width);
+                let dz = (at(i, front) - at(i, back)) / ((front - back) as Float * cell_depth);
+
+                normals.push(Vec3::new(-dx, 1.0, -dz).unit_vector());
+            }
+        }
+
+        let mut bounds = Vec::with_capacity((columns - 1) * (rows - 1));
+        for j in 0..rows - 1 {
+            for i in 0..columns - 1 {
+                let corners = [at(i, j), at(i + 1, j), at(i, j + 1), at(i + 1, j + 1)];
+
+                bounds.push((
+                    corners.iter().cloned().fold(Float::MAX, min_float),
+                    corners.iter().cloned().fold(Float::MIN, max_float)
+                ));
+            }
+        }
+
+        let min_height = heights.iter().cloned().fold(Float::MAX, min_float);
+        let max_height = heights.iter().cloned().fold(Float::MIN, max_float);
+
+        Self {
+            obj_box: BoundingBox::new(
+                Vec3::new(bottom_left.x, min_height - 0.0001, bottom_left.z),
+                Vec3::new(bottom_left.x + size.x, max_height + 0.0001, bottom_left.z + size.z)
+            ),
+            bottom_left,
+            cell_width,
+            cell_depth,
+            columns,
+            rows,
+            heights,
+            normals,
+            bounds,
+            material
+        }
+    }
+
+    pub fn from_image(image: &DynamicImage, bottom_left: Vec3, size: Vec3, material: Rc<dyn Material>) -> Self {
+        let luma = image.to_luma8();
+        let (columns, rows) = luma.dimensions();
+        let heights = luma.pixels().map(|pixel| pixel[0] as Float / 255.0).collect();
+
+        Self::new(heights, columns as usize, rows as usize, bottom_left, size, material)
+    }
+
+    //samples the function over [0, 1] x [0, 1]
+    pub fn from_fn<F: Fn(Float, Float) -> Float>(function: F, columns: usize, rows: usize, bottom_left: Vec3, size: Vec3, material: Rc<dyn Material>) -> Self {
+        let mut heights = Vec::with_capacity(columns * rows);
+
+        for j in 0..rows {
+            for i in 0..columns {
+                heights.push(function(i as Float / (columns - 1) as Float, j as Float / (rows - 1) as Float));
+            }
+        }
+
+        Self::new(heights, columns, rows, bottom_left, size, material)
+    }
+
+    fn vertex(&self, i: usize, j: usize) -> Vec3 {
+        Vec3::new(
+            self.bottom_left.x + i as Float * self.cell_width,
+            self.heights[j * self.columns + i],
+            self.bottom_left.z + j as Float * self.cell_depth
+        )
+    }
+
+    fn normal(&self, i: usize, j: usize) -> Vec3 {
+        self.normals[j * self.columns + i]
+    }
+
+    fn hit_cell(&self, ray: &Ray, i: usize, j: usize, tmin: Float, tmax: Float) -> Option<HitRecord> {
+        let corners = [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)];
+        let triangles = [[corners[0], corners[1], corners[2]], [corners[0], corners[2], corners[3]]];
+        let mut result = None;
+        let mut closest = tmax;
+
+        for (idx, [a, b, c]) in triangles.iter().enumerate() {
+            if let Some((time, beta, gamma)) = intersect_triangle(
+                ray, self.vertex(a.0, a.1), self.vertex(b.0, b.1), self.vertex(c.0, c.1), tmin, closest
+            ) {
+                closest = time;
+                result = Some((time, idx, beta, gamma));
+            }
+        }
+
+        let (time, idx, beta, gamma) = result?;
+        let [a, b, c] = triangles[idx];
+        let alpha = 1.0 - beta - gamma;
+        let normal = self.normal(a.0, a.1) * alpha + self.normal(b.0, b.1) * beta + self.normal(c.0, c.1) * gamma;
+        let point = ray.at(time);
+
+        Some(HitRecord::new(
+            ray,
+            point,
+            normal.unit_vector(),
+            time,
+            (point.x - self.bottom_left.x) / (self.cell_width * (self.columns - 1) as Float),
+            (point.z - self.bottom_left.z) / (self.cell_depth * (self.rows - 1) as Float),
+            self.material.clone()
+        ))
+    }
+}
+
+impl Hittable for Heightfield {
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
+        let (start, end) = self.obj_box.clip(ray, tmin, tmax)?;
+        let columns = self.columns - 1;
+        let rows = self.rows - 1;
+
+        let entry = ray.at(start);
+        let mut i = (((entry.x - self.bottom_left.x) / self.cell_width).floor().max(0.0) as usize).min(columns - 1);
+        let mut j = (((entry.z - self.bottom_left.z) / self.cell_depth).floor().max(0.0) as usize).min(rows - 1);
+
+        let step = |direction: Float, cell: usize, size: Float, origin: Float, base: Float| -> (Float, Float) {
+            if direction > 0.0 {
+                ((base + (cell + 1) as Float * size - origin) / direction, size / direction)
+            }
+            else if direction < 0.0 {
+                ((base + cell as Float * size - origin) / direction, -size / direction)
+            }
+            else {
+                (Float::MAX, Float::MAX)
+            }
+        };
+
+        let (mut next_x, delta_x) = step(ray.direction.x, i, self.cell_width, ray.origin.x, self.bottom_left.x);
+        let (mut next_z, delta_z) = step(ray.direction.z, j, self.cell_depth, ray.origin.z, self.bottom_left.z);
+
+        let mut time = start;
+        loop {
+            let exit = min_float(min_float(next_x, next_z), end);
+            let (low, high) = self.bounds[j * columns + i];
+            let y0 = ray.origin.y + ray.direction.y * time;
+            let y1 = ray.origin.y + ray.direction.y * exit;
+
+            if min_float(y0, y1) <= high && max_float(y0, y1) >= low {
+                if let Some(record) = self.hit_cell(ray, i, j, tmin, tmax) {
+                    return Some(record);
+                }
+            }
+
+            if exit >= end {
+                return None;
+            }
+
+            if next_x < next_z {
+                if ray.direction.x > 0.0 { i += 1 } else if i == 0 { return None } else { i -= 1 }
+                time = next_x;
+                next_x += delta_x;
+            }
+            else {
+                if ray.direction.z > 0.0 { j += 1 } else if j == 0 { return None } else { j -= 1 }
+                time = next_z;
+                next_z += delta_z;
+            }
+
+            if i >= columns || j >= rows {
+                return None;
+            }
+        }
+    }
+
+    fn bounding_box(&self, _t0: Float, _t1: Float) -> Option<BoundingBox> {
+        Some(self.obj_box)
+    }
+}
@@ -0,0 +1,369 @@
+use super::hittable::{HitRecord, Hittable};
+use super::material::material::Material;
+use super::bounding::{BoundingBox, BvhNode};
+use super::vec3::{Vec3, PackedVec3};
+use super::ray::Ray;
+use super::{gamma, Float};
+
+use std::rc::Rc;
+
+//meshes keep their vertices in single precision, big meshes are mostly memory traffic
+//the bounds and the intersection both use the widened vertices, so the stored triangles stay exactly inside their boxes
+fn pack(points: &[Vec3]) -> Vec<PackedVec3> {
+    points.iter().map(|point| PackedVec3::from(*point)).collect()
+}
+
+#[allow(clippy::unnecessary_cast)]
+fn pack_uv(uv: (Float, Float)) -> (f32, f32) {
+    (uv.0 as f32, uv.1 as f32)
+}
+
+#[allow(clippy::unnecessary_cast)]
+fn widen_uv(uv: (f32, f32)) -> (Float, Float) {
+    (uv.0 as Float, uv.1 as Float)
+}
+
+//vertex positions sampled at evenly spaced times between time0 and time1
+pub struct VertexMotion {
+    pub samples: Vec<Vec<PackedVec3>>,
+    pub time0: Float,
+    pub time1: Float
+}
+
+impl VertexMotion {
+    pub fn new(samples: Vec<Vec<Vec3>>, time0: Float, time1: Float) -> Self {
+        if samples.is_empty() || samples.iter().any(|sample| sample.len() != samples[0].len()) {
+            panic!("Every motion sample needs the same number of vertices");
+        }
+
+        Self {
+            samples: samples.iter().map(|sample| pack(sample)).collect(),
+            time0,
+            time1
+        }
+    }
+
+    fn sample_time(&self, idx: usize) -> Float {
+        if self.samples.len() == 1 {
+            return self.time0;
+        }
+
+        self.time0 + (self.time1 - self.time0) * idx as Float / (self.samples.len() - 1) as Float
+    }
+
+    pub fn position(&self, vertex: usize, time: Float) -> Vec3 {
+        let last = self.samples.len() - 1;
+
+        if last == 0 || time <= self.time0 {
+            return self.samples[0][vertex].into();
+        }
+        if time >= self.time1 {
+            return self.samples[last][vertex].into();
+        }
+
+        let x = (time - self.time0) / (self.time1 - self.time0) * last as Float;
+        let idx = (x.floor() as usize).min(last - 1);
+        let t = x - idx as Float;
+
+        Vec3::from(self.samples[idx][vertex]) * (1.0 - t) + Vec3::from(self.samples[idx + 1][vertex]) * t
+    }
+
+    //positions are linear between samples, so the ends and the samples in between bound the motion
+    fn positions_between(&self, vertex: usize, t0: Float, t1: Float) -> Vec<Vec3> {
+        let mut result = vec![self.position(vertex, t0), self.position(vertex, t1)];
+
+        for idx in 0..self.samples.len() {
+            let time = self.sample_time(idx);
+
+            if time > t0 && time < t1 {
+                result.push(self.samples[idx][vertex].into());
+            }
+        }
+
+        result
+    }
+}
+
+pub struct MeshData {
+    pub positions: Vec<PackedVec3>,
+    pub normals: Option<Vec<PackedVec3>>,
+    pub uvs: Option<Vec<(f32, f32)>>,
+    pub indices: Vec<[usize; 3]>,
+    pub motion: Option<VertexMotion>,
+    pub material: Rc<dyn Material>
+}
+
+impl MeshData {
+    pub fn new(positions: Vec<Vec3>, normals: Option<Vec<Vec3>>, uvs: Option<Vec<(Float, Float)>>, indices: Vec<[usize; 3]>, material: Rc<dyn Material>) -> Self {
+        Self {
+            positions: pack(&positions),
+            normals: normals.map(|normals| pack(&normals)),
+            uvs: uvs.map(|uvs| uvs.into_iter().map(pack_uv).collect()),
+            indices,
+            motion: None,
+            material
+        }
+    }
+
+    pub fn deforming(motion: VertexMotion, uvs: Option<Vec<(Float, Float)>>, indices: Vec<[usize; 3]>, material: Rc<dyn Material>) -> Self {
+        Self {
+            positions: motion.samples[0].clone(),
+            normals: None,
+            uvs: uvs.map(|uvs| uvs.into_iter().map(pack_uv).collect()),
+            indices,
+            motion: Some(motion),
+            material
+        }
+    }
+
+    pub fn position(&self, vertex: usize, time: Float) -> Vec3 {
+        match &self.motion {
+            Some(motion) => motion.position(vertex, time),
+            None => self.positions[vertex].into()
+        }
+    }
+
+    pub fn normal(&self, vertex: usize) -> Option<Vec3> {
+        self.normals.as_ref().map(|normals| normals[vertex].into())
+    }
+
+    pub fn uv(&self, vertex: usize) -> Option<(Float, Float)> {
+        self.uvs.as_ref().map(|uvs| widen_uv(uvs[vertex]))
+    }
+
+    pub fn shutter(&self) -> (Float, Float) {
+        match &self.motion {
+            Some(motion) => (motion.time0, motion.time1),
+            None => (0.0, 1.0)
+        }
+    }
+
+    pub fn smooth_normals(&self) -> Vec<PackedVec3> {
+        let mut normals = vec![Vec3::default(); self.positions.len()];
+
+        for [a, b, c] in self.indices.iter() {
+            let (pa, pb, pc) = (Vec3::from(self.positions[*a]), Vec3::from(self.positions[*b]), Vec3::from(self.positions[*c]));
+            let face = Vec3::cross_product(pb - pa, pc - pa);
+
+            for idx in [*a, *b, *c].iter() {
+                normals[*idx] = normals[*idx] + face;
+            }
+        }
+
+        normals.iter()
+            .map(|normal| if normal.squared_length() > 0.0 { normal.unit_vector() } else { Vec3::new(0.0, 1.0, 0.0) })
+            .map(PackedVec3::from)
+            .collect()
+    }
+}
+
+//watertight test of Woop et al. as in pbrt, the vertices are moved so the ray runs along +z from the origin
+//and the edge functions of a shared edge are computed the same way from both sides, no ray slips between two triangles
+#[allow(clippy::unnecessary_cast)]
+pub fn intersect_triangle(ray: &Ray, a: Vec3, b: Vec3, c: Vec3, tmin: Float, tmax: Float) -> Option<(Float, Float, Float)> {
+    let direction = ray.direction;
+    let kz = if direction.x.abs() > direction.y.abs() && direction.x.abs() > direction.z.abs() { 0 } else if direction.y.abs() > direction.z.abs() { 1 } else { 2 };
+    let (kx, ky) = ((kz + 1) % 3, (kz + 2) % 3);
+
+    let permute = |vector: Vec3| Vec3::new(vector[kx], vector[ky], vector[kz]);
+    let direction = permute(direction);
+
+    let shear_x = -direction.x / direction.z;
+    let shear_y = -direction.y / direction.z;
+    let shear_z = 1.0 / direction.z;
+
+    let mut points = [permute(a - ray.origin), permute(b - ray.origin), permute(c - ray.origin)];
+    for point in points.iter_mut() {
+        point.x += shear_x * point.z;
+        point.y += shear_y * point.z;
+    }
+    let [p0, p1, p2] = points;
+
+    let mut e0 = p1.x * p2.y - p1.y * p2.x;
+    let mut e1 = p2.x * p0.y - p2.y * p0.x;
+    let mut e2 = p0.x * p1.y - p0.y * p1.x;
+
+    //an edge function of exactly 0 in single precision may have lost its sign, double precision settles the side
+    if cfg!(feature = "f32") && (e0 == 0.0 || e1 == 0.0 || e2 == 0.0) {
+        e0 = (p1.x as f64 * p2.y as f64 - p1.y as f64 * p2.x as f64) as Float;
+        e1 = (p2.x as f64 * p0.y as f64 - p2.y as f64 * p0.x as f64) as Float;
+        e2 = (p0.x as f64 * p1.y as f64 - p0.y as f64 * p1.x as f64) as Float;
+    }
+
+    if (e0 < 0.0 || e1 < 0.0 || e2 < 0.0) && (e0 > 0.0 || e1 > 0.0 || e2 > 0.0) {
+        return None;
+    }
+
+    let determinant = e0 + e1 + e2;
+
+    if determinant == 0.0 {
+        return None;
+    }
+
+    let (z0, z1, z2) = (p0.z * shear_z, p1.z * shear_z, p2.z * shear_z);
+    let inverse = 1.0 / determinant;
+    let time = (e0 * z0 + e1 * z1 + e2 * z2) * inverse;
+
+    //pbrt's bound on the rounding error of time, a hit within it of tmin could be on either side
+    let max_x = p0.x.abs().max(p1.x.abs()).max(p2.x.abs());
+    let max_y = p0.y.abs().max(p1.y.abs()).max(p2.y.abs());
+    let max_z = z0.abs().max(z1.abs()).max(z2.abs());
+    let max_e = e0.abs().max(e1.abs()).max(e2.abs());
+
+    let delta_x = gamma(5) * (max_x + max_z);
+    let delta_y = gamma(5) * (max_y + max_z);
+    let delta_z = gamma(3) * max_z;
+    let delta_e = 2.0 * (gamma(2) * max_x * max_y + delta_y * max_x + delta_x * max_y);
+    let delta_time = 3.0 * (gamma(3) * max_e * max_z + delta_e * max_z + delta_z * max_e) * inverse.abs();
+
+    if time - delta_time <= tmin || time > tmax {
+        return None;
+    }
+
+    Some((time, e1 * inverse, e2 * inverse))
+}
+
+pub struct MeshTriangle {
+    mesh: Rc<MeshData>,
+    index: usize
+}
+
+impl MeshTriangle {
+    pub fn new(mesh: Rc<MeshData>, index: usize) -> Self {
+        Self {
+            mesh,
+            index
+        }
+    }
+
+    fn vertices(&self, time: Float) -> (Vec3, Vec3, Vec3) {
+        let [a, b, c] = self.mesh.indices[self.index];
+        (self.mesh.position(a, time), self.mesh.position(b, time), self.mesh.position(c, time))
+    }
+}
+
+impl Hittable for MeshTriangle {
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
+        let (a, b, c) = self.vertices(ray.time);
+        let (time, beta, gamma) = intersect_triangle(ray, a, b, c, tmin, tmax)?;
+        let alpha = 1.0 - beta - gamma;
+        let [ia, ib, ic] = self.mesh.indices[self.index];
+
+        let geometric = Vec3::cross_product(b - a, c - a).unit_vector();
+        let shading = match (self.mesh.normal(ia), self.mesh.normal(ib), self.mesh.normal(ic)) {
+            (Some(na), Some(nb), Some(nc)) => (na * alpha + nb * beta + nc * gamma).unit_vector(),
+            _ => geometric
+        };
+        //the vertex normals say which side is outside, the flat normal which side the ray is on
+        let outward = if Vec3::dot_product(geometric, shading) < 0.0 { -geometric } else { geometric };
+
+        let uvs = match (self.mesh.uv(ia), self.mesh.uv(ib), self.mesh.uv(ic)) {
+            (Some(ua), Some(ub), Some(uc)) => Some([ua, ub, uc]),
+            _ => None
+        };
+
+        let (u, v) = match &uvs {
+            Some(uvs) => (
+                uvs[0].0 * alpha + uvs[1].0 * beta + uvs[2].0 * gamma,
+                uvs[0].1 * alpha + uvs[1].1 * beta + uvs[2].1 * gamma
+            ),
+            None => (beta, gamma)
+        };
+
+        //solves the edges against the uv deltas, degenerate uvs fall back to the edges themselves
+        let (dpdu, dpdv) = match &uvs {
+            Some(uvs) => {
+                let (du1, dv1) = (uvs[1].0 - uvs[0].0, uvs[1].1 - uvs[0].1);
+                let (du2, dv2) = (uvs[2].0 - uvs[0].0, uvs[2].1 - uvs[0].1);
+                let determinant = du1 * dv2 - dv1 * du2;
+
+                //zero up to the rounding of the products, a fixed cutoff would mean something else in each precision
+                if determinant.abs() <= super::gamma(3) * ((du1 * dv2).abs() + (dv1 * du2).abs()) {
+                    (b - a, c - a)
+                }
+                else {
+                    (
+                        ((b - a) * dv2 - (c - a) * dv1) / determinant,
+                        ((c - a) * du1 - (b - a) * du2) / determinant
+                    )
+                }
+            },
+            None => (b - a, c - a)
+        };
+
+        let mut record = HitRecord::new(
+            ray,
+            ray.at(time),
+            outward,
+            time,
+            u,
+            v,
+            self.mesh.material.clone()
+        );
+
+        record.normal = if record.front_face { shading } else { -shading };
+
+        record.dpdu = dpdu;
+        record.dpdv = dpdv;
+        Some(record)
+    }
+
+    fn bounding_box(&self, t0: Float, t1: Float) -> Option<BoundingBox> {
+        let obj_box = match &self.mesh.motion {
+            Some(motion) => {
+                let points: Vec<Vec3> = self.mesh.indices[self.index].iter()
+                    .flat_map(|vertex| motion.positions_between(*vertex, t0, t1))
+                    .collect();
+
+                BoundingBox::from_points(&points)
+            },
+            None => {
+                let (a, b, c) = self.vertices(t0);
+                BoundingBox::from_points(&[a, b, c])
+            }
+        };
+        let padding = Vec3::new(0.0001, 0.0001, 0.0001);
+
+        Some(BoundingBox::new(obj_box.min - padding, obj_box.max + padding))
+    }
+}
+
+pub struct TriangleMesh {
+    data: Rc<MeshData>,
+    bvh: BvhNode
+}
+
+impl TriangleMesh {
+    pub fn new(data: MeshData) -> Self {
+        let data = Rc::new(data);
+        let mut triangles: Vec<Rc<dyn Hittable>> = (0..data.indices.len())
+            .map(|index| Rc::new(MeshTriangle::new(data.clone(), index)) as Rc<dyn Hittable>)
+            .collect();
+
+        let size = triangles.len();
+        let (time0, time1) = data.shutter();
+
+        Self {
+            bvh: BvhNode::new(&mut triangles, 0, size, time0, time1),
+            data
+        }
+    }
+
+    pub fn data(&self) -> &MeshData {
+        &self.data
+    }
+}
+
+impl Hittable for TriangleMesh {
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
+        self.bvh.hit(ray, tmin, tmax)
+    }
+
+    fn occluded(&self, ray: &Ray, tmin: Float, tmax: Float) -> bool {
+        self.bvh.occluded(ray, tmin, tmax)
+    }
+
+    fn bounding_box(&self, t0: Float, t1: Float) -> Option<BoundingBox> {
+        self.bvh.bounding_box(t0, t1)
+    }
+}
@@ -0,0 +1,242 @@
+use super::hittable::{HitRecord, Hittable};
+use super::material::material::Material;
+use super::bounding::BoundingBox;
+use super::roots::{solve_quadratic, solve_polynomial};
+use super::vec3::Vec3;
+use super::ray::Ray;
+use super::Float;
+use super::consts::PI;
+
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+const LEAF_SIZE: usize = 4;
+
+#[derive(Clone, Copy)]
+pub struct Metaball {
+    pub center: Vec3,
+    pub radius: Float,
+    pub weight: Float
+}
+
+impl Metaball {
+    pub fn new(center: Vec3, radius: Float, weight: Float) -> Self {
+        Self {
+            center,
+            radius,
+            weight
+        }
+    }
+
+    //wyvill falloff w (1 - s)^3 with s the squared distance over the squared radius, zero from the radius on
+    fn gradient(&self, point: Vec3) -> Vec3 {
+        let offset = point - self.center;
+        let s = offset.squared_length() / (self.radius * self.radius);
+
+        if s >= 1.0 {
+            return Vec3::default();
+        }
+
+        offset * (-6.0 * self.weight * (1.0 - s).powi(2) / (self.radius * self.radius))
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        let extent = Vec3::new(self.radius, self.radius, self.radius);
+        BoundingBox::new(self.center - extent, self.center + extent)
+    }
+
+    fn interval(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<(Float, Float)> {
+        let oc = ray.origin - self.center;
+        let roots = solve_quadratic(
+            ray.direction.squared_length(),
+            2.0 * Vec3::dot_product(oc, ray.direction),
+            oc.squared_length() - self.radius * self.radius
+        );
+
+        if roots.len() < 2 {
+            return None;
+        }
+
+        let (t0, t1) = (roots[0].max(tmin), roots[1].min(tmax));
+        if t0 < t1 { Some((t0, t1)) } else { None }
+    }
+}
+
+//leaves own balls[start..start + count], inner nodes have count 0 and their left child right after them
+struct Node {
+    obj_box: BoundingBox,
+    start: usize,
+    count: usize,
+    right: usize
+}
+
+pub struct Metaballs {
+    balls: Vec<Metaball>,
+    nodes: Vec<Node>,
+    threshold: Float,
+    material: Rc<dyn Material>
+}
+
+impl Metaballs {
+    pub fn new(mut balls: Vec<Metaball>, threshold: Float, material: Rc<dyn Material>) -> Self {
+        if balls.is_empty() {
+            panic!("Metaballs need at least one ball");
+        }
+
+        let mut nodes = vec![];
+        let size = balls.len();
+        Self::build(&mut balls, &mut nodes, 0, size);
+
+        Self {
+            balls,
+            nodes,
+            threshold,
+            material
+        }
+    }
+
+    fn build(balls: &mut [Metaball], nodes: &mut Vec<Node>, start: usize, end: usize) -> usize {
+        let obj_box = balls[start + 1..end].iter()
+            .fold(balls[start].bounding_box(), |acc, ball| BoundingBox::surrounding_box(&acc, &ball.bounding_box()));
+
+        let idx = nodes.len();
+        nodes.push(Node { obj_box, start, count: end - start, right: 0 });
+
+        if end - start <= LEAF_SIZE {
+            return idx;
+        }
+
+        let extent = obj_box.max - obj_box.min;
+        let axis = if extent.x > extent.y && extent.x > extent.z { 0 } else if extent.y > extent.z { 1 } else { 2 };
+
+        balls[start..end].sort_by(|a, b| a.center[axis].partial_cmp(&b.center[axis]).unwrap_or(Ordering::Equal));
+
+        let mid = start + (end - start) / 2;
+        Self::build(balls, nodes, start, mid);
+        let right = Self::build(balls, nodes, mid, end);
+
+        nodes[idx].count = 0;
+        nodes[idx].right = right;
+        idx
+    }
+
+    //only the balls whose influence the ray crosses, with the part of the ray inside it
+    fn candidates(&self, ray: &Ray, tmin: Float, tmax: Float) -> Vec<(usize, Float, Float)> {
+        let mut result = vec![];
+        let mut stack = vec![0];
+
+        while let Some(idx) = stack.pop() {
+            let node = &self.nodes[idx];
+
+            if !node.obj_box.hit(ray, tmin, tmax) {
+                continue;
+            }
+
+            if node.count == 0 {
+                stack.push(node.right);
+                stack.push(idx + 1);
+                continue;
+            }
+
+            for ball in node.start..node.start + node.count {
+                if let Some((t0, t1)) = self.balls[ball].interval(ray, tmin, tmax) {
+                    result.push((ball, t0, t1));
+                }
+            }
+        }
+
+        result
+    }
+
+    //the field minus the threshold along start..end as a degree 6 polynomial in the fraction of the way from start to end
+    fn polynomial(&self, ray: &Ray, active: &[usize], start: Float, end: Float) -> [Float; 7] {
+        let mut result = [0.0; 7];
+        result[6] = -self.threshold;
+
+        let direction = ray.direction * (end - start);
+
+        for ball in active.iter().map(|ball| &self.balls[*ball]) {
+            let offset = ray.at(start) - ball.center;
+            let radius_squared = ball.radius * ball.radius;
+
+            //1 - s, the falloff is its cube
+            let q = [
+                -direction.squared_length() / radius_squared,
+                -2.0 * Vec3::dot_product(offset, direction) / radius_squared,
+                1.0 - offset.squared_length() / radius_squared
+            ];
+
+            let mut cube = [0.0; 7];
+            for (i, a) in q.iter().enumerate() {
+                for (j, b) in q.iter().enumerate() {
+                    for (k, c) in q.iter().enumerate() {
+                        cube[i + j + k] += a * b * c;
+                    }
+                }
+            }
+
+            for (coefficient, term) in result.iter_mut().zip(cube.iter()) {
+                *coefficient += ball.weight * term;
+            }
+        }
+
+        result
+    }
+
+    fn get_hit_record(&self, ray: &Ray, active: &[usize], time: Float) -> HitRecord {
+        let point = ray.at(time);
+        let gradient = active.iter().fold(Vec3::default(), |acc, ball| acc + self.balls[*ball].gradient(point));
+        let normal = if gradient.squared_length() > 0.0 { -gradient.unit_vector() } else { -ray.direction.unit_vector() };
+
+        HitRecord::new(
+            ray,
+            point,
+            normal,
+            time,
+            1.0 - (normal.z.atan2(normal.x) + PI) / (2.0 * PI),
+            (num::clamp(normal.y, -1.0, 1.0).asin() + PI / 2.0) / PI,
+            self.material.clone()
+        )
+    }
+}
+
+impl Hittable for Metaballs {
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
+        let candidates = self.candidates(ray, tmin, tmax);
+
+        let mut breaks: Vec<Float> = candidates.iter().flat_map(|(_, t0, t1)| vec![*t0, *t1]).collect();
+        breaks.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+        //the set of overlapping balls only changes at the breaks, between them the field is smooth
+        for segment in breaks.windows(2) {
+            let (start, end) = (segment[0], segment[1]);
+            let middle = (start + end) / 2.0;
+
+            if end - start <= 0.0 {
+                continue;
+            }
+
+            let active: Vec<usize> = candidates.iter()
+                .filter(|(_, t0, t1)| *t0 <= middle && middle <= *t1)
+                .map(|(ball, _, _)| *ball)
+                .collect();
+
+            if active.is_empty() {
+                continue;
+            }
+
+            //inside a segment every active ball is within its radius, so the falloff is exactly this polynomial
+            let polynomial = self.polynomial(ray, &active, start, end);
+
+            if let Some(x) = solve_polynomial(&polynomial, 0.0, 1.0).into_iter().find(|x| *x > 0.0) {
+                return Some(self.get_hit_record(ray, &active, start + (end - start) * x));
+            }
+        }
+
+        None
+    }
+
+    fn bounding_box(&self, _t0: Float, _t1: Float) -> Option<BoundingBox> {
+        Some(self.nodes[0].obj_box)
+    }
+}
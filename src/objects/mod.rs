@@ -1,12 +1,31 @@
 pub mod constant_medium;
 pub mod moving_sphere;
 pub mod rectangles;
+pub mod polygons;
 pub mod sphere;
 pub mod cube;
+pub mod disk;
+pub mod cylinder;
+pub mod cone;
+pub mod torus;
+pub mod paraboloid;
+pub mod plane;
+pub mod csg;
+pub mod sdf;
+pub mod heightfield;
+pub mod mesh;
+pub mod bezier;
+pub mod subdivision;
+pub mod curves;
+pub mod displacement;
+pub mod metaballs;
+pub mod point_cloud;
 
-use super::utils::{max_f64, min_f64};
+use super::utils::{max_float, min_float, f32_above, f32_below, gamma, Float, consts};
 use super::utils::ray;
+use super::utils::roots;
 use super::utils::vec3;
+use super::utils::color;
 use super::hittable::*;
 use super::material;
 use super::textures;
\ No newline at end of file
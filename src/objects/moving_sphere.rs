@@ -3,21 +3,22 @@ use super::material::material::Material;
 use super::bounding::BoundingBox;
 use super::vec3::Vec3;
 use super::ray::Ray;
+use super::Float;
+use super::consts::PI;
 
-use std::f64::consts::PI;
 use std::rc::Rc;
 
 pub struct MovingSphere {
     center0: Vec3,
     center1: Vec3,
-    time0: f64,
-    time1: f64,
-    radius: f64,
+    time0: Float,
+    time1: Float,
+    radius: Float,
     material: Rc<dyn Material>
 }
 
 impl MovingSphere {
-    pub fn new(center0: Vec3, center1: Vec3, time0: f64, time1: f64, radius: f64, material: Rc<dyn Material>) -> Self {
+    pub fn new(center0: Vec3, center1: Vec3, time0: Float, time1: Float, radius: Float, material: Rc<dyn Material>) -> Self {
         Self {
             center0,
             center1,
@@ -28,14 +29,14 @@ impl MovingSphere {
         }
     }
 
-    fn find_center(&self, time: f64) -> Vec3 {
+    fn find_center(&self, time: Float) -> Vec3 {
         self.center0 + (self.center1 - self.center0) * ((time - self.time0) / (self.time1 - self.time0))
     }
 
-    fn get_hit_record(&self, ray: &Ray, time: f64) -> HitRecord {
+    fn get_hit_record(&self, ray: &Ray, time: Float) -> HitRecord {
         let point = ray.at(time);
         let (u, v) = self.get_uv((point - self.find_center(time)) / self.radius);
-        HitRecord::new(
+        let mut record = HitRecord::new(
             ray,
             point,
             (point - self.find_center(time)) / self.radius,
@@ -43,19 +44,34 @@ impl MovingSphere {
             u,
             v,
             self.material.clone()
-        )
+        );
+
+        (record.dpdu, record.dpdv) = self.get_tangents(point - self.find_center(time));
+        record
     }
 
-    fn get_uv(&self, point: Vec3) -> (f64, f64) {
+    fn get_tangents(&self, local: Vec3) -> (Vec3, Vec3) {
+        let radial = (local.x * local.x + local.z * local.z).sqrt();
+        let dpdu = Vec3::new(local.z, 0.0, -local.x) * (2.0 * PI);
+
+        if radial < 1e-8 {
+            return (dpdu, Vec3::default());
+        }
+
+        let dpdv = Vec3::new(-local.x * local.y / radial, radial, -local.z * local.y / radial) * PI;
+        (dpdu, dpdv)
+    }
+
+    fn get_uv(&self, point: Vec3) -> (Float, Float) {
         let phi = point.z.atan2(point.x);
         let theta = point.y.asin();
         
-        (1.0 - (phi + PI) / 2.0 * PI, (theta + PI / 2.0) / PI)
+        (1.0 - (phi + PI) / (2.0 * PI), (theta + PI / 2.0) / PI)
     }
 }
 
 impl Hittable for MovingSphere {
-    fn hit(&self, ray: &Ray, tmin: f64, tmax: f64) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
         let oc = ray.origin - self.find_center(ray.time);
         let a = ray.direction.squared_length();
         let b = Vec3::dot_product(oc, ray.direction);
@@ -79,7 +95,26 @@ impl Hittable for MovingSphere {
         None
     }
 
-    fn bounding_box(&self, t0: f64, t1: f64) -> Option<BoundingBox> {
+    fn hit_all(&self, ray: &Ray, tmin: Float, tmax: Float) -> Vec<HitRecord> {
+        let oc = ray.origin - self.find_center(ray.time);
+        let a = ray.direction.squared_length();
+        let b = Vec3::dot_product(oc, ray.direction);
+        let c = oc.squared_length() - self.radius * self.radius;
+        let discriminant = b * b - a * c;
+
+        if discriminant <= 0.0 {
+            return vec![];
+        }
+
+        let root = discriminant.sqrt();
+
+        [(-b - root) / a, (-b + root) / a].iter()
+            .filter(|time| **time < tmax && **time > tmin)
+            .map(|time| self.get_hit_record(ray, *time))
+            .collect()
+    }
+
+    fn bounding_box(&self, t0: Float, t1: Float) -> Option<BoundingBox> {
         let min = BoundingBox::new(
             self.find_center(t0) - Vec3::new(self.radius, self.radius, self.radius),
             self.find_center(t0) + Vec3::new(self.radius, self.radius, self.radius)
@@ -0,0 +1,107 @@
+use super::hittable::{HitRecord, Hittable, HittableList};
+use super::material::material::Material;
+use super::bounding::BoundingBox;
+use super::disk::Disk;
+use super::vec3::Vec3;
+use super::ray::Ray;
+use super::{gamma, Float};
+use super::consts::PI;
+
+use std::rc::Rc;
+
+pub struct Paraboloid {
+    center: Vec3,
+    radius: Float,
+    height: Float,
+    caps: HittableList,
+    material: Rc<dyn Material>
+}
+
+impl Paraboloid {
+    pub fn new(center: Vec3, radius: Float, height: Float, capped: bool, material: Rc<dyn Material>) -> Self {
+        let mut caps = HittableList::new();
+
+        if capped {
+            caps.push(Rc::new(Disk::new(
+                center + Vec3::new(0.0, height, 0.0), radius,
+                material.clone()
+            )));
+        }
+
+        Self {
+            center,
+            radius,
+            height,
+            caps,
+            material
+        }
+    }
+
+    fn get_hit_record(&self, ray: &Ray, time: Float) -> HitRecord {
+        let point = ray.at(time);
+        let local = point - self.center;
+        let k = self.radius * self.radius / self.height;
+        let phi = local.z.atan2(local.x);
+
+        HitRecord::new(
+            ray,
+            point,
+            Vec3::new(2.0 * local.x, -k, 2.0 * local.z).unit_vector(),
+            time,
+            1.0 - (phi + PI) / (2.0 * PI),
+            local.y / self.height,
+            self.material.clone()
+        )
+    }
+
+    fn hit_side(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
+        let oc = ray.origin - self.center;
+        let direction = ray.direction;
+        let k = self.radius * self.radius / self.height;
+
+        let a = direction.x * direction.x + direction.z * direction.z;
+        let b = 2.0 * (oc.x * direction.x + oc.z * direction.z) - k * direction.y;
+        let c = oc.x * oc.x + oc.z * oc.z - k * oc.y;
+
+        //a is zero up to rounding when the ray runs along the axis, then only the linear term is left
+        let times = if a <= gamma(2) * direction.squared_length() {
+            if b == 0.0 { vec![] } else { vec![-c / b] }
+        }
+        else {
+            let discriminant = b * b - 4.0 * a * c;
+
+            if discriminant <= 0.0 {
+                return None;
+            }
+
+            let root = discriminant.sqrt();
+            vec![(-b - root) / (2.0 * a), (-b + root) / (2.0 * a)]
+        };
+
+        for time in times {
+            let y = oc.y + direction.y * time;
+
+            if time < tmax && time > tmin && y >= 0.0 && y <= self.height {
+                return Some(self.get_hit_record(ray, time));
+            }
+        }
+
+        None
+    }
+}
+
+impl Hittable for Paraboloid {
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
+        match self.hit_side(ray, tmin, tmax) {
+            Some(record) => self.caps.hit(ray, tmin, record.time).or(Some(record)),
+            None => self.caps.hit(ray, tmin, tmax)
+        }
+    }
+
+    fn bounding_box(&self, _t0: Float, _t1: Float) -> Option<BoundingBox> {
+        Some(BoundingBox::new(
+            self.center - Vec3::new(self.radius, 0.0, self.radius),
+            self.center + Vec3::new(self.radius, self.height, self.radius)
+        ))
+    }
+}
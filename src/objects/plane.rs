@@ -0,0 +1,70 @@
+use super::hittable::{HitRecord, Hittable};
+use super::material::material::Material;
+use super::bounding::BoundingBox;
+use super::vec3::Vec3;
+use super::ray::Ray;
+use super::Float;
+
+use std::rc::Rc;
+
+pub struct Plane {
+    point: Vec3,
+    normal: Vec3,
+    u_axis: Vec3,
+    v_axis: Vec3,
+    material: Rc<dyn Material>
+}
+
+impl Plane {
+    pub fn new(point: Vec3, normal: Vec3, material: Rc<dyn Material>) -> Self {
+        let normal = normal.unit_vector();
+        let helper = if normal.x.abs() > 0.9 { Vec3::new(0.0, 1.0, 0.0) } else { Vec3::new(1.0, 0.0, 0.0) };
+        let u_axis = Vec3::cross_product(helper, normal).unit_vector();
+        let v_axis = Vec3::cross_product(normal, u_axis);
+
+        Self {
+            point,
+            normal,
+            u_axis,
+            v_axis,
+            material
+        }
+    }
+}
+
+impl Hittable for Plane {
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
+        let denominator = Vec3::dot_product(self.normal, ray.direction);
+
+        if denominator.abs() < 1e-8 {
+            return None;
+        }
+
+        let time = Vec3::dot_product(self.point - ray.origin, self.normal) / denominator;
+
+        if time < tmin || time > tmax {
+            return None;
+        }
+
+        let point = ray.at(time);
+        let local = point - self.point;
+
+        let mut record = HitRecord::new(
+            ray,
+            point,
+            self.normal,
+            time,
+            Vec3::dot_product(local, self.u_axis).rem_euclid(1.0),
+            Vec3::dot_product(local, self.v_axis).rem_euclid(1.0),
+            self.material.clone()
+        );
+
+        record.dpdu = self.u_axis;
+        record.dpdv = self.v_axis;
+        Some(record)
+    }
+
+    fn bounding_box(&self, _t0: Float, _t1: Float) -> Option<BoundingBox> {
+        None
+    }
+}
@@ -0,0 +1,288 @@
+use super::hittable::{HitRecord, Hittable};
+use super::material::material::Material;
+use super::bounding::BoundingBox;
+use super::color::Color;
+use super::{f32_above, f32_below};
+use super::vec3::Vec3;
+use super::ray::Ray;
+use super::{gamma, Float};
+use super::consts::PI;
+
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+const LEAF_SIZE: usize = 8;
+
+pub enum PointShape {
+    Sphere,
+    //oriented by the point normal, or facing the ray when the cloud has none
+    Disc
+}
+
+//single precision storage, a few million points fit in a few hundred megabytes
+#[derive(Clone, Copy)]
+struct Point {
+    position: [f32; 3],
+    normal: [f32; 3],
+    color: [u8; 3]
+}
+
+impl Point {
+    fn position(&self) -> Vec3 {
+        Vec3::new(self.position[0] as Float, self.position[1] as Float, self.position[2] as Float)
+    }
+
+    fn normal(&self) -> Vec3 {
+        Vec3::new(self.normal[0] as Float, self.normal[1] as Float, self.normal[2] as Float)
+    }
+
+    fn color(&self) -> Color {
+        Color::new(self.color[0] as Float / 255.0, self.color[1] as Float / 255.0, self.color[2] as Float / 255.0)
+    }
+}
+
+//32 bytes, leaves own points[offset..offset + count], inner nodes have count 0, the left child right after them and the right one at offset
+struct Node {
+    min: [f32; 3],
+    max: [f32; 3],
+    offset: u32,
+    count: u32
+}
+
+impl Node {
+    fn bounding_box(&self) -> BoundingBox {
+        BoundingBox::new(
+            Vec3::new(self.min[0] as Float, self.min[1] as Float, self.min[2] as Float),
+            Vec3::new(self.max[0] as Float, self.max[1] as Float, self.max[2] as Float)
+        )
+    }
+}
+
+fn quantize(value: Float) -> u8 {
+    (num::clamp(value, 0.0, 1.0) * 255.0).round() as u8
+}
+
+pub struct PointCloud {
+    points: Vec<Point>,
+    nodes: Vec<Node>,
+    radius: Float,
+    shape: PointShape,
+    oriented: bool,
+    colored: bool,
+    material: Rc<dyn Material>
+}
+
+impl PointCloud {
+    #[allow(clippy::unnecessary_cast)]
+    //the hit record carries the point's color when there are colors, the material multiplies its albedo by it
+    pub fn new(positions: &[Vec3], colors: Option<&[Color]>, normals: Option<&[Vec3]>, radius: Float, shape: PointShape, material: Rc<dyn Material>) -> Self {
+        if positions.is_empty() || colors.is_some_and(|colors| colors.len() != positions.len()) || normals.is_some_and(|normals| normals.len() != positions.len()) {
+            panic!("A point cloud needs at least one position (and a color and normal for each, if any)");
+        }
+
+        let mut points: Vec<Point> = positions.iter().enumerate()
+            .map(|(idx, position)| {
+                let normal = normals.map_or(Vec3::default(), |normals| normals[idx].unit_vector());
+                let color = colors.map_or(Color::new(1.0, 1.0, 1.0), |colors| colors[idx]);
+
+                Point {
+                    position: [position.x as f32, position.y as f32, position.z as f32],
+                    normal: [normal.x as f32, normal.y as f32, normal.z as f32],
+                    color: [quantize(color.r), quantize(color.g), quantize(color.b)]
+                }
+            })
+            .collect();
+
+        let mut nodes = Vec::with_capacity(2 * points.len() / LEAF_SIZE + 1);
+        let size = points.len();
+        Self::build(&mut points, &mut nodes, 0, size, radius);
+
+        Self {
+            points,
+            nodes,
+            radius,
+            shape,
+            oriented: normals.is_some(),
+            colored: colors.is_some(),
+            material
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    fn build(points: &mut [Point], nodes: &mut Vec<Node>, start: usize, end: usize, radius: Float) -> usize {
+        let mut min = [Float::MAX; 3];
+        let mut max = [Float::MIN; 3];
+
+        for point in points[start..end].iter() {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(point.position[axis] as Float - radius);
+                max[axis] = max[axis].max(point.position[axis] as Float + radius);
+            }
+        }
+
+        let idx = nodes.len();
+        nodes.push(Node {
+            min: [f32_below(min[0]), f32_below(min[1]), f32_below(min[2])],
+            max: [f32_above(max[0]), f32_above(max[1]), f32_above(max[2])],
+            offset: start as u32,
+            count: (end - start) as u32
+        });
+
+        if end - start <= LEAF_SIZE {
+            return idx;
+        }
+
+        let axis = (0..3).max_by(|a, b| (max[*a] - min[*a]).partial_cmp(&(max[*b] - min[*b])).unwrap_or(Ordering::Equal)).unwrap();
+        let mid = (end - start) / 2;
+
+        points[start..end].select_nth_unstable_by(mid, |a, b| a.position[axis].partial_cmp(&b.position[axis]).unwrap_or(Ordering::Equal));
+
+        Self::build(points, nodes, start, start + mid, radius);
+        let right = Self::build(points, nodes, start + mid, end, radius);
+
+        nodes[idx].offset = right as u32;
+        nodes[idx].count = 0;
+        idx
+    }
+
+    fn intersect(&self, point: &Point, ray: &Ray, tmin: Float, tmax: Float) -> Option<(Float, Vec3)> {
+        let center = point.position();
+
+        match self.shape {
+            PointShape::Sphere => {
+                let oc = ray.origin - center;
+                let a = ray.direction.squared_length();
+                let b = Vec3::dot_product(oc, ray.direction);
+                let c = oc.squared_length() - self.radius * self.radius;
+                let discriminant = b * b - a * c;
+
+                if discriminant <= 0.0 {
+                    return None;
+                }
+
+                let root = discriminant.sqrt();
+
+                [(-b - root) / a, (-b + root) / a].iter()
+                    .find(|time| **time > tmin && **time < tmax)
+                    .map(|time| (*time, (ray.at(*time) - center) / self.radius))
+            },
+            PointShape::Disc => {
+                let normal = if self.oriented { point.normal() } else { -ray.direction.unit_vector() };
+                let denominator = Vec3::dot_product(normal, ray.direction);
+
+                if denominator.abs() <= gamma(3) * ray.direction.length() {
+                    return None;
+                }
+
+                let time = Vec3::dot_product(center - ray.origin, normal) / denominator;
+
+                if time <= tmin || time >= tmax || (ray.at(time) - center).squared_length() > self.radius * self.radius {
+                    return None;
+                }
+
+                Some((time, normal))
+            }
+        }
+    }
+}
+
+impl Hittable for PointCloud {
+    fn hit(&self, ray: &Ray, tmin: Float, mut tmax: Float) -> Option<HitRecord> {
+        let mut closest: Option<(Float, Vec3, usize)> = None;
+        let mut stack = vec![0];
+
+        while let Some(idx) = stack.pop() {
+            let node = &self.nodes[idx];
+
+            if !node.bounding_box().hit(ray, tmin, tmax) {
+                continue;
+            }
+
+            if node.count > 0 {
+                let start = node.offset as usize;
+
+                for point in start..start + node.count as usize {
+                    if let Some((time, normal)) = self.intersect(&self.points[point], ray, tmin, tmax) {
+                        tmax = time;
+                        closest = Some((time, normal, point));
+                    }
+                }
+
+                continue;
+            }
+
+            //nearer child on top of the stack so it can shrink tmax before the other is tested
+            let left = self.nodes[idx + 1].bounding_box().clip(ray, tmin, tmax).map(|(t0, _)| t0);
+            let right = self.nodes[node.offset as usize].bounding_box().clip(ray, tmin, tmax).map(|(t0, _)| t0);
+
+            match (left, right) {
+                (Some(left), Some(right)) if right < left => {
+                    stack.push(idx + 1);
+                    stack.push(node.offset as usize);
+                },
+                (Some(_), Some(_)) => {
+                    stack.push(node.offset as usize);
+                    stack.push(idx + 1);
+                },
+                (Some(_), None) => stack.push(idx + 1),
+                (None, Some(_)) => stack.push(node.offset as usize),
+                (None, None) => {}
+            }
+        }
+
+        let (time, normal, point) = closest?;
+
+        let mut record = HitRecord::new(
+            ray,
+            ray.at(time),
+            normal,
+            time,
+            1.0 - (normal.z.atan2(normal.x) + PI) / (2.0 * PI),
+            (num::clamp(normal.y, -1.0, 1.0).asin() + PI / 2.0) / PI,
+            self.material.clone()
+        );
+
+        if self.colored {
+            record.color = Some(self.points[point].color());
+        }
+
+        Some(record)
+    }
+
+    fn occluded(&self, ray: &Ray, tmin: Float, tmax: Float) -> bool {
+        let mut stack = vec![0];
+
+        while let Some(idx) = stack.pop() {
+            let node = &self.nodes[idx];
+
+            if !node.bounding_box().hit(ray, tmin, tmax) {
+                continue;
+            }
+
+            if node.count == 0 {
+                stack.push(node.offset as usize);
+                stack.push(idx + 1);
+                continue;
+            }
+
+            let start = node.offset as usize;
+
+            if self.points[start..start + node.count as usize].iter().any(|point| self.intersect(point, ray, tmin, tmax).is_some()) {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn bounding_box(&self, _t0: Float, _t1: Float) -> Option<BoundingBox> {
+        Some(self.nodes[0].bounding_box())
+    }
+}
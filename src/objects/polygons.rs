@@ -0,0 +1,327 @@
+use super::hittable::{HitRecord, Hittable};
+use super::material::material::Material;
+use super::bounding::BoundingBox;
+use super::vec3::Vec3;
+use super::ray::Ray;
+use super::Float;
+
+use std::rc::Rc;
+
+use rand::Rng;
+
+fn planar_box(points: &[Vec3]) -> BoundingBox {
+    let obj_box = BoundingBox::from_points(points);
+    let padding = Vec3::new(0.0001, 0.0001, 0.0001);
+
+    BoundingBox::new(obj_box.min - padding, obj_box.max + padding)
+}
+
+fn plane_time(ray: &Ray, normal: Vec3, distance: Float, tmin: Float, tmax: Float) -> Option<Float> {
+    let denominator = Vec3::dot_product(normal, ray.direction);
+
+    if denominator.abs() < 1e-8 {
+        return None;
+    }
+
+    let time = (distance - Vec3::dot_product(normal, ray.origin)) / denominator;
+
+    if time < tmin || time > tmax {
+        return None;
+    }
+
+    Some(time)
+}
+
+//solid angle density of sampling a point on a flat light uniformly by area
+pub fn light_pdf(object: &dyn Hittable, origin: &Vec3, direction: &Vec3, area: Float) -> Float {
+    let ray = Ray::new(*origin, *direction, 0.0);
+
+    if let Some(record) = object.hit(&ray, 0.001, Float::MAX) {
+        let distance_squared = record.time * record.time * direction.squared_length();
+        let cosine = (Vec3::dot_product(*direction, record.normal) / direction.length()).abs();
+
+        distance_squared / (cosine * area)
+    }
+    else {
+        0.0
+    }
+}
+
+pub struct Quad {
+    origin: Vec3,
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+    normal: Vec3,
+    distance: Float,
+    area: Float,
+    material: Rc<dyn Material>
+}
+
+impl Quad {
+    pub fn new(origin: Vec3, u: Vec3, v: Vec3, material: Rc<dyn Material>) -> Self {
+        let n = Vec3::cross_product(u, v);
+        let normal = n.unit_vector();
+
+        Self {
+            origin,
+            u,
+            v,
+            w: n / n.squared_length(),
+            normal,
+            distance: Vec3::dot_product(normal, origin),
+            area: n.length(),
+            material
+        }
+    }
+}
+
+impl Hittable for Quad {
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
+        let time = plane_time(ray, self.normal, self.distance, tmin, tmax)?;
+        let point = ray.at(time);
+        let planar = point - self.origin;
+
+        let alpha = Vec3::dot_product(self.w, Vec3::cross_product(planar, self.v));
+        let beta = Vec3::dot_product(self.w, Vec3::cross_product(self.u, planar));
+
+        if !(0.0..=1.0).contains(&alpha) || !(0.0..=1.0).contains(&beta) {
+            return None;
+        }
+
+        let mut record = HitRecord::new(
+            ray,
+            point,
+            self.normal,
+            time,
+            alpha,
+            beta,
+            self.material.clone()
+        );
+
+        record.dpdu = self.u;
+        record.dpdv = self.v;
+        Some(record)
+    }
+
+    fn bounding_box(&self, _t0: Float, _t1: Float) -> Option<BoundingBox> {
+        Some(planar_box(&[
+            self.origin,
+            self.origin + self.u,
+            self.origin + self.v,
+            self.origin + self.u + self.v
+        ]))
+    }
+
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> Float {
+        light_pdf(self, origin, direction, self.area)
+    }
+
+    fn random(&self, origin: &Vec3) -> Vec3 {
+        let mut rng = rand::thread_rng();
+        let point = self.origin + self.u * rng.gen_range(0.0, 1.0) + self.v * rng.gen_range(0.0, 1.0);
+
+        point - *origin
+    }
+}
+
+pub struct Triangle {
+    a: Vec3,
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+    normal: Vec3,
+    distance: Float,
+    area: Float,
+    material: Rc<dyn Material>
+}
+
+impl Triangle {
+    pub fn new(a: Vec3, b: Vec3, c: Vec3, material: Rc<dyn Material>) -> Self {
+        let u = b - a;
+        let v = c - a;
+        let n = Vec3::cross_product(u, v);
+        let normal = n.unit_vector();
+
+        Self {
+            a,
+            u,
+            v,
+            w: n / n.squared_length(),
+            normal,
+            distance: Vec3::dot_product(normal, a),
+            area: n.length() / 2.0,
+            material
+        }
+    }
+}
+
+impl Hittable for Triangle {
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
+        let time = plane_time(ray, self.normal, self.distance, tmin, tmax)?;
+        let point = ray.at(time);
+        let planar = point - self.a;
+
+        let alpha = Vec3::dot_product(self.w, Vec3::cross_product(planar, self.v));
+        let beta = Vec3::dot_product(self.w, Vec3::cross_product(self.u, planar));
+
+        if alpha < 0.0 || beta < 0.0 || alpha + beta > 1.0 {
+            return None;
+        }
+
+        let mut record = HitRecord::new(
+            ray,
+            point,
+            self.normal,
+            time,
+            alpha,
+            beta,
+            self.material.clone()
+        );
+
+        record.dpdu = self.u;
+        record.dpdv = self.v;
+        Some(record)
+    }
+
+    fn bounding_box(&self, _t0: Float, _t1: Float) -> Option<BoundingBox> {
+        Some(planar_box(&[self.a, self.a + self.u, self.a + self.v]))
+    }
+
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> Float {
+        light_pdf(self, origin, direction, self.area)
+    }
+
+    fn random(&self, origin: &Vec3) -> Vec3 {
+        let mut rng = rand::thread_rng();
+        let mut alpha = rng.gen_range(0.0, 1.0);
+        let mut beta = rng.gen_range(0.0, 1.0);
+
+        if alpha + beta > 1.0 {
+            alpha = 1.0 - alpha;
+            beta = 1.0 - beta;
+        }
+
+        self.a + self.u * alpha + self.v * beta - *origin
+    }
+}
+
+pub struct Polygon {
+    vertices: Vec<Vec3>,
+    normal: Vec3,
+    distance: Float,
+    u_axis: Vec3,
+    v_axis: Vec3,
+    uv_min: (Float, Float),
+    uv_size: (Float, Float),
+    areas: Vec<Float>,
+    area: Float,
+    material: Rc<dyn Material>
+}
+
+impl Polygon {
+    pub fn new(vertices: Vec<Vec3>, material: Rc<dyn Material>) -> Self {
+        if vertices.len() < 3 {
+            panic!("A polygon needs at least 3 vertices");
+        }
+
+        let origin = vertices[0];
+        let normal = Vec3::cross_product(vertices[1] - origin, vertices[2] - origin).unit_vector();
+        let u_axis = (vertices[1] - origin).unit_vector();
+        let v_axis = Vec3::cross_product(normal, u_axis);
+
+        let mut areas = vec![];
+        let mut area = 0.0;
+
+        for idx in 1..vertices.len() - 1 {
+            area += Vec3::cross_product(vertices[idx] - origin, vertices[idx + 1] - origin).length() / 2.0;
+            areas.push(area);
+        }
+
+        let projected: Vec<(Float, Float)> = vertices.iter()
+            .map(|vertex| (Vec3::dot_product(*vertex, u_axis), Vec3::dot_product(*vertex, v_axis)))
+            .collect();
+
+        let u_min = projected.iter().map(|p| p.0).fold(Float::MAX, Float::min);
+        let u_max = projected.iter().map(|p| p.0).fold(Float::MIN, Float::max);
+        let v_min = projected.iter().map(|p| p.1).fold(Float::MAX, Float::min);
+        let v_max = projected.iter().map(|p| p.1).fold(Float::MIN, Float::max);
+
+        Self {
+            normal,
+            distance: Vec3::dot_product(normal, origin),
+            u_axis,
+            v_axis,
+            uv_min: (u_min, v_min),
+            uv_size: (u_max - u_min, v_max - v_min),
+            areas,
+            area,
+            vertices,
+            material
+        }
+    }
+
+    fn contains(&self, point: Vec3) -> bool {
+        let count = self.vertices.len();
+
+        (0..count).all(|idx| {
+            let edge = self.vertices[(idx + 1) % count] - self.vertices[idx];
+            let to_point = point - self.vertices[idx];
+
+            Vec3::dot_product(Vec3::cross_product(edge, to_point), self.normal) >= 0.0
+        })
+    }
+}
+
+impl Hittable for Polygon {
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
+        let time = plane_time(ray, self.normal, self.distance, tmin, tmax)?;
+        let point = ray.at(time);
+
+        if !self.contains(point) {
+            return None;
+        }
+
+        let mut record = HitRecord::new(
+            ray,
+            point,
+            self.normal,
+            time,
+            (Vec3::dot_product(point, self.u_axis) - self.uv_min.0) / self.uv_size.0,
+            (Vec3::dot_product(point, self.v_axis) - self.uv_min.1) / self.uv_size.1,
+            self.material.clone()
+        );
+
+        record.dpdu = self.u_axis * self.uv_size.0;
+        record.dpdv = self.v_axis * self.uv_size.1;
+        Some(record)
+    }
+
+    fn bounding_box(&self, _t0: Float, _t1: Float) -> Option<BoundingBox> {
+        Some(planar_box(&self.vertices))
+    }
+
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> Float {
+        light_pdf(self, origin, direction, self.area)
+    }
+
+    fn random(&self, origin: &Vec3) -> Vec3 {
+        let mut rng = rand::thread_rng();
+        let target = rng.gen_range(0.0, self.area);
+        let idx = self.areas.iter().position(|area| target <= *area).unwrap_or(self.areas.len() - 1);
+
+        let a = self.vertices[0];
+        let u = self.vertices[idx + 1] - a;
+        let v = self.vertices[idx + 2] - a;
+
+        let mut alpha = rng.gen_range(0.0, 1.0);
+        let mut beta = rng.gen_range(0.0, 1.0);
+
+        if alpha + beta > 1.0 {
+            alpha = 1.0 - alpha;
+            beta = 1.0 - beta;
+        }
+
+        a + u * alpha + v * beta - *origin
+    }
+}
@@ -1,22 +1,47 @@
 use super::hittable::{HitRecord, Hittable};
 use super::material::material::Material;
 use super::bounding::BoundingBox;
+use super::polygons::light_pdf;
 use super::vec3::Vec3;
 use super::ray::Ray;
+use super::Float;
 
 use std::rc::Rc;
 
+use rand::Rng;
+
+//the record of a hit on an axis aligned rectangle from min to max, u runs along axis a and v along axis b
+pub fn rectangle_record(ray: &Ray, time: Float, a: usize, b: usize, min: Vec3, max: Vec3, material: Rc<dyn Material>) -> HitRecord {
+    let point = ray.at(time);
+    let mut normal = Vec3::default();
+    normal[3 - a - b] = 1.0;
+
+    let mut record = HitRecord::new(
+        ray,
+        point,
+        normal,
+        time,
+        (point[a] - min[a]) / (max[a] - min[a]),
+        (point[b] - min[b]) / (max[b] - min[b]),
+        material
+    );
+
+    record.dpdu[a] = max[a] - min[a];
+    record.dpdv[b] = max[b] - min[b];
+    record
+}
+
 pub struct XYRectangle {
-    x0: f64, 
-    x1: f64,
-    y0: f64,
-    y1: f64,
-    z: f64,
+    x0: Float, 
+    x1: Float,
+    y0: Float,
+    y1: Float,
+    z: Float,
     material: Rc<dyn Material>
 }
 
 impl XYRectangle {
-    pub fn new(x0: f64, x1: f64, y0: f64, y1: f64, z: f64, material: Rc<dyn Material>) -> Self {
+    pub fn new(x0: Float, x1: Float, y0: Float, y1: Float, z: Float, material: Rc<dyn Material>) -> Self {
         Self {
             x0,
             x1,
@@ -29,7 +54,7 @@ impl XYRectangle {
 }
 
 impl Hittable for XYRectangle {
-    fn hit(&self, ray: &Ray, tmin: f64, tmax: f64) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
         let time = (self.z - ray.origin.z) / ray.direction.z;
     
         if time < tmin || time > tmax {
@@ -43,36 +68,39 @@ impl Hittable for XYRectangle {
             return None;
         }
 
-        Some(HitRecord::new(
-            ray,
-            ray.at(time),
-            Vec3::new(0.0, 0.0, 1.0),
-            time,
-            (x - self.x0) / (self.x1 - self.x0),
-            (y - self.y0) / (self.y1 - self.y0),
-            self.material.clone()
-        ))
+        Some(rectangle_record(ray, time, 0, 1, Vec3::new(self.x0, self.y0, self.z), Vec3::new(self.x1, self.y1, self.z), self.material.clone()))
     }
 
-    fn bounding_box(&self, _t0: f64, _t1: f64) -> Option<BoundingBox> {
+    fn bounding_box(&self, _t0: Float, _t1: Float) -> Option<BoundingBox> {
         Some(BoundingBox::new(
             Vec3::new(self.x0, self.y0, self.z - 0.0001),
             Vec3::new(self.x1, self.y1, self.z + 0.0001)
         ))
     }
+
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> Float {
+        light_pdf(self, origin, direction, (self.x1 - self.x0) * (self.y1 - self.y0))
+    }
+
+    fn random(&self, origin: &Vec3) -> Vec3 {
+        let mut rng = rand::thread_rng();
+        let point = Vec3::new(rng.gen_range(self.x0, self.x1), rng.gen_range(self.y0, self.y1), self.z);
+
+        point - *origin
+    }
 }
 
 pub struct XZRectangle {
-    x0: f64, 
-    x1: f64,
-    z0: f64,
-    z1: f64,
-    y: f64,
+    x0: Float, 
+    x1: Float,
+    z0: Float,
+    z1: Float,
+    y: Float,
     material: Rc<dyn Material>
 }
 
 impl XZRectangle {
-    pub fn new(x0: f64, x1: f64, z0: f64, z1: f64, y: f64, material: Rc<dyn Material>) -> Self {
+    pub fn new(x0: Float, x1: Float, z0: Float, z1: Float, y: Float, material: Rc<dyn Material>) -> Self {
         Self {
             x0,
             x1,
@@ -85,7 +113,7 @@ impl XZRectangle {
 }
 
 impl Hittable for XZRectangle {
-    fn hit(&self, ray: &Ray, tmin: f64, tmax: f64) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
         let time = (self.y - ray.origin.y) / ray.direction.y;
     
         if time < tmin || time > tmax {
@@ -99,37 +127,40 @@ impl Hittable for XZRectangle {
             return None;
         }
 
-        Some(HitRecord::new(
-            ray,
-            ray.at(time),
-            Vec3::new(0.0, 1.0, 0.0),
-            time,
-            (x - self.x0) / (self.x1 - self.x0),
-            (z - self.z0) / (self.z1 - self.z0),
-            self.material.clone()
-        ))
+        Some(rectangle_record(ray, time, 0, 2, Vec3::new(self.x0, self.y, self.z0), Vec3::new(self.x1, self.y, self.z1), self.material.clone()))
     }
 
-    fn bounding_box(&self, _t0: f64, _t1: f64) -> Option<BoundingBox> {
+    fn bounding_box(&self, _t0: Float, _t1: Float) -> Option<BoundingBox> {
         Some(BoundingBox::new(
             Vec3::new(self.x0, self.y - 0.0001, self.z0),
             Vec3::new(self.x1, self.y + 0.0001, self.z1)
         ))
     }
+
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> Float {
+        light_pdf(self, origin, direction, (self.x1 - self.x0) * (self.z1 - self.z0))
+    }
+
+    fn random(&self, origin: &Vec3) -> Vec3 {
+        let mut rng = rand::thread_rng();
+        let point = Vec3::new(rng.gen_range(self.x0, self.x1), self.y, rng.gen_range(self.z0, self.z1));
+
+        point - *origin
+    }
 }
 
 
 pub struct YZRectangle {
-    y0: f64, 
-    y1: f64,
-    z0: f64,
-    z1: f64,
-    x: f64,
+    y0: Float, 
+    y1: Float,
+    z0: Float,
+    z1: Float,
+    x: Float,
     material: Rc<dyn Material>
 }
 
 impl YZRectangle {
-    pub fn new(y0: f64, y1: f64, z0: f64, z1: f64, x: f64, material: Rc<dyn Material>) -> Self {
+    pub fn new(y0: Float, y1: Float, z0: Float, z1: Float, x: Float, material: Rc<dyn Material>) -> Self {
         Self {
             y0,
             y1,
@@ -142,7 +173,7 @@ impl YZRectangle {
 }
 
 impl Hittable for YZRectangle {
-    fn hit(&self, ray: &Ray, tmin: f64, tmax: f64) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
         let time = (self.x - ray.origin.x) / ray.direction.x;
     
         if time < tmin || time > tmax {
@@ -156,22 +187,25 @@ impl Hittable for YZRectangle {
             return None;
         }
 
-        Some(HitRecord::new(
-            ray,
-            ray.at(time),
-            Vec3::new(1.0, 0.0, 0.0),
-            time,
-            (y - self.y0) / (self.y1 - self.y0),
-            (z - self.z0) / (self.z1 - self.z0),
-            self.material.clone()
-        ))
+        Some(rectangle_record(ray, time, 1, 2, Vec3::new(self.x, self.y0, self.z0), Vec3::new(self.x, self.y1, self.z1), self.material.clone()))
     }
 
-    fn bounding_box(&self, _t0: f64, _t1: f64) -> Option<BoundingBox> {
+    fn bounding_box(&self, _t0: Float, _t1: Float) -> Option<BoundingBox> {
         Some(BoundingBox::new(
             Vec3::new(self.x - 0.0001, self.y0, self.z0),
             Vec3::new(self.x + 0.0001, self.y1, self.z1),
         ))
     }
+
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> Float {
+        light_pdf(self, origin, direction, (self.y1 - self.y0) * (self.z1 - self.z0))
+    }
+
+    fn random(&self, origin: &Vec3) -> Vec3 {
+        let mut rng = rand::thread_rng();
+        let point = Vec3::new(self.x, rng.gen_range(self.y0, self.y1), rng.gen_range(self.z0, self.z1));
+
+        point - *origin
+    }
 }
 
@@ -0,0 +1,207 @@
+use super::hittable::{HitRecord, Hittable};
+use super::material::material::Material;
+use super::bounding::BoundingBox;
+use super::{min_float, max_float};
+use super::vec3::Vec3;
+use super::ray::Ray;
+use super::Float;
+use super::consts::PI;
+
+use std::rc::Rc;
+
+use num::clamp;
+
+const MAX_STEPS: usize = 512;
+const EPSILON: Float = 0.0001;
+
+pub type Sdf = Rc<dyn Fn(Vec3) -> Float>;
+
+pub struct SdfObject {
+    distance: Sdf,
+    obj_box: BoundingBox,
+    material: Rc<dyn Material>
+}
+
+impl SdfObject {
+    pub fn new(distance: Sdf, obj_box: BoundingBox, material: Rc<dyn Material>) -> Self {
+        Self {
+            distance,
+            obj_box,
+            material
+        }
+    }
+
+    fn get_normal(&self, point: Vec3) -> Vec3 {
+        let dx = Vec3::new(EPSILON, 0.0, 0.0);
+        let dy = Vec3::new(0.0, EPSILON, 0.0);
+        let dz = Vec3::new(0.0, 0.0, EPSILON);
+
+        Vec3::new(
+            (self.distance)(point + dx) - (self.distance)(point - dx),
+            (self.distance)(point + dy) - (self.distance)(point - dy),
+            (self.distance)(point + dz) - (self.distance)(point - dz)
+        ).unit_vector()
+    }
+
+    fn get_uv(normal: Vec3) -> (Float, Float) {
+        let phi = normal.z.atan2(normal.x);
+        let theta = clamp(normal.y, -1.0, 1.0).asin();
+
+        (1.0 - (phi + PI) / (2.0 * PI), (theta + PI / 2.0) / PI)
+    }
+}
+
+impl Hittable for SdfObject {
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
+        let (start, end) = self.obj_box.clip(ray, tmin, tmax)?;
+        let length = ray.direction.length();
+        let sign = if (self.distance)(ray.at(start)) < 0.0 { -1.0 } else { 1.0 };
+
+        let mut time = start;
+        for _ in 0..MAX_STEPS {
+            let point = ray.at(time);
+            let distance = sign * (self.distance)(point);
+
+            if distance < EPSILON {
+                let normal = self.get_normal(point);
+                let (u, v) = Self::get_uv(normal);
+
+                return Some(HitRecord::new(
+                    ray,
+                    point,
+                    normal,
+                    time,
+                    u,
+                    v,
+                    self.material.clone()
+                ));
+            }
+
+            time += distance / length;
+            if time > end {
+                return None;
+            }
+        }
+
+        None
+    }
+
+    fn bounding_box(&self, _t0: Float, _t1: Float) -> Option<BoundingBox> {
+        Some(self.obj_box)
+    }
+}
+
+fn abs(v: Vec3) -> Vec3 {
+    Vec3::new(v.x.abs(), v.y.abs(), v.z.abs())
+}
+
+fn max(v: Vec3, value: Float) -> Vec3 {
+    Vec3::new(max_float(v.x, value), max_float(v.y, value), max_float(v.z, value))
+}
+
+pub fn sphere(center: Vec3, radius: Float) -> Sdf {
+    Rc::new(move |point| (point - center).length() - radius)
+}
+
+pub fn rounded_box(center: Vec3, half_size: Vec3, radius: Float) -> Sdf {
+    Rc::new(move |point| {
+        let q = abs(point - center) - half_size + Vec3::new(radius, radius, radius);
+
+        max(q, 0.0).length() + min_float(max_float(q.x, max_float(q.y, q.z)), 0.0) - radius
+    })
+}
+
+pub fn capsule(a: Vec3, b: Vec3, radius: Float) -> Sdf {
+    Rc::new(move |point| {
+        let pa = point - a;
+        let ba = b - a;
+        let h = clamp(Vec3::dot_product(pa, ba) / ba.squared_length(), 0.0, 1.0);
+
+        (pa - ba * h).length() - radius
+    })
+}
+
+pub fn torus(center: Vec3, major_radius: Float, minor_radius: Float) -> Sdf {
+    Rc::new(move |point| {
+        let p = point - center;
+        let ring = (p.x * p.x + p.z * p.z).sqrt() - major_radius;
+
+        (ring * ring + p.y * p.y).sqrt() - minor_radius
+    })
+}
+
+pub fn mandelbulb(center: Vec3, scale: Float, power: Float, iterations: usize) -> Sdf {
+    Rc::new(move |point| {
+        let c = (point - center) / scale;
+        let mut z = c;
+        let mut dr = 1.0;
+        let mut r = z.length();
+
+        for _ in 0..iterations {
+            if r > 2.0 {
+                break;
+            }
+
+            let theta = (z.z / r).acos() * power;
+            let phi = z.y.atan2(z.x) * power;
+            dr = r.powf(power - 1.0) * power * dr + 1.0;
+
+            z = Vec3::new(theta.sin() * phi.cos(), theta.sin() * phi.sin(), theta.cos()) * r.powf(power) + c;
+            r = z.length();
+        }
+
+        0.5 * r.ln() * r / dr * scale
+    })
+}
+
+pub fn union(a: Sdf, b: Sdf) -> Sdf {
+    Rc::new(move |point| min_float(a(point), b(point)))
+}
+
+pub fn intersection(a: Sdf, b: Sdf) -> Sdf {
+    Rc::new(move |point| max_float(a(point), b(point)))
+}
+
+pub fn difference(a: Sdf, b: Sdf) -> Sdf {
+    Rc::new(move |point| max_float(a(point), -b(point)))
+}
+
+pub fn smooth_union(a: Sdf, b: Sdf, k: Float) -> Sdf {
+    Rc::new(move |point| {
+        let da = a(point);
+        let db = b(point);
+        let h = clamp(0.5 + 0.5 * (db - da) / k, 0.0, 1.0);
+
+        db * (1.0 - h) + da * h - k * h * (1.0 - h)
+    })
+}
+
+pub fn translate(a: Sdf, offset: Vec3) -> Sdf {
+    Rc::new(move |point| a(point - offset))
+}
+
+//twists around the Y axis, the distance is scaled down since the twist stretches space
+pub fn twist(a: Sdf, k: Float) -> Sdf {
+    Rc::new(move |point| {
+        let (sin, cos) = (k * point.y).sin_cos();
+        let radius = (point.x * point.x + point.z * point.z).sqrt();
+        let q = Vec3::new(cos * point.x - sin * point.z, point.y, sin * point.x + cos * point.z);
+
+        a(q) / (1.0 + (k * radius).powi(2)).sqrt()
+    })
+}
+
+//a period of zero along an axis disables the repetition on that axis
+pub fn repeat(a: Sdf, period: Vec3) -> Sdf {
+    Rc::new(move |point| {
+        let mut q = point;
+
+        for idx in 0..3 {
+            if period[idx] != 0.0 {
+                q[idx] = point[idx] - period[idx] * (point[idx] / period[idx]).round();
+            }
+        }
+
+        a(q)
+    })
+}
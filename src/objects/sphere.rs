@@ -3,18 +3,19 @@ use super::material::material::Material;
 use super::bounding::BoundingBox;
 use super::vec3::Vec3;
 use super::ray::Ray;
+use super::Float;
+use super::consts::PI;
 
-use std::f64::consts::PI;
 use std::rc::Rc;
 
 pub struct Sphere {
     center: Vec3,
-    radius: f64,
+    radius: Float,
     material: Rc<dyn Material>
 }
 
 impl Sphere {
-    pub fn new(center: Vec3, radius: f64, material: Rc<dyn Material>) -> Self {
+    pub fn new(center: Vec3, radius: Float, material: Rc<dyn Material>) -> Self {
         Self {
             center,
             radius,
@@ -22,10 +23,10 @@ impl Sphere {
         }
     }
 
-    fn get_hit_record(&self, ray: &Ray, time: f64) -> HitRecord {
+    fn get_hit_record(&self, ray: &Ray, time: Float) -> HitRecord {
         let point = ray.at(time);
         let (u, v) = self.get_uv((point - self.center) / self.radius);
-        HitRecord::new(
+        let mut record = HitRecord::new(
             ray,
             point,
             (point - self.center) / self.radius,
@@ -33,19 +34,34 @@ impl Sphere {
             u,
             v,
             self.material.clone()
-        )
+        );
+
+        (record.dpdu, record.dpdv) = self.get_tangents(point - self.center);
+        record
     }
 
-    fn get_uv(&self, point: Vec3) -> (f64, f64) {
+    fn get_tangents(&self, local: Vec3) -> (Vec3, Vec3) {
+        let radial = (local.x * local.x + local.z * local.z).sqrt();
+        let dpdu = Vec3::new(local.z, 0.0, -local.x) * (2.0 * PI);
+
+        if radial < 1e-8 {
+            return (dpdu, Vec3::default());
+        }
+
+        let dpdv = Vec3::new(-local.x * local.y / radial, radial, -local.z * local.y / radial) * PI;
+        (dpdu, dpdv)
+    }
+
+    fn get_uv(&self, point: Vec3) -> (Float, Float) {
         let phi = point.z.atan2(point.x);
         let theta = point.y.asin();
         
-        (1.0 - (phi + PI) / 2.0 * PI, (theta + PI / 2.0) / PI)
+        (1.0 - (phi + PI) / (2.0 * PI), (theta + PI / 2.0) / PI)
     }
 }
 
 impl Hittable for Sphere {
-    fn hit(&self, ray: &Ray, tmin: f64, tmax: f64) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
         let oc = ray.origin - self.center;
         let a = ray.direction.squared_length();
         let b = Vec3::dot_product(oc, ray.direction);
@@ -69,7 +85,26 @@ impl Hittable for Sphere {
         None
     }
 
-    fn bounding_box(&self, _t0: f64, _t1: f64) -> Option<BoundingBox> {
+    fn hit_all(&self, ray: &Ray, tmin: Float, tmax: Float) -> Vec<HitRecord> {
+        let oc = ray.origin - self.center;
+        let a = ray.direction.squared_length();
+        let b = Vec3::dot_product(oc, ray.direction);
+        let c = oc.squared_length() - self.radius * self.radius;
+        let discriminant = b * b - a * c;
+
+        if discriminant <= 0.0 {
+            return vec![];
+        }
+
+        let root = discriminant.sqrt();
+
+        [(-b - root) / a, (-b + root) / a].iter()
+            .filter(|time| **time < tmax && **time > tmin)
+            .map(|time| self.get_hit_record(ray, *time))
+            .collect()
+    }
+
+    fn bounding_box(&self, _t0: Float, _t1: Float) -> Option<BoundingBox> {
         Some(BoundingBox::new(
             self.center - Vec3::new(self.radius, self.radius, self.radius),
             self.center + Vec3::new(self.radius, self.radius, self.radius)
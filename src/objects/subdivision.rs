@@ -0,0 +1,172 @@
+use super::material::material::Material;
+use super::mesh::{MeshData, TriangleMesh};
+use super::vec3::Vec3;
+use super::Float;
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+#[derive(Clone)]
+pub struct ControlMesh {
+    pub positions: Vec<Vec3>,
+    pub faces: Vec<Vec<usize>>
+}
+
+impl ControlMesh {
+    pub fn new(positions: Vec<Vec3>, faces: Vec<Vec<usize>>) -> Self {
+        for face in faces.iter() {
+            if face.len() < 3 || face.iter().any(|idx| *idx >= positions.len()) {
+                panic!("Every face of a control mesh needs at least 3 vertices, all of them existing positions");
+            }
+
+            if face.iter().enumerate().any(|(k, idx)| face[k + 1..].contains(idx)) {
+                panic!("A face of a control mesh can't use the same vertex twice");
+            }
+        }
+
+        Self {
+            positions,
+            faces
+        }
+    }
+
+    pub fn subdivide(&self, levels: usize) -> Self {
+        let mut result = self.clone();
+
+        for _ in 0..levels {
+            result = result.catmull_clark();
+        }
+
+        result
+    }
+
+    pub fn catmull_clark(&self) -> Self {
+        let vertex_count = self.positions.len();
+
+        let face_points: Vec<Vec3> = self.faces.iter()
+            .map(|face| face.iter().fold(Vec3::default(), |acc, idx| acc + self.positions[*idx]) / face.len() as Float)
+            .collect();
+
+        let mut edges: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut edge_faces: Vec<Vec<usize>> = vec![];
+        let mut edge_vertices: Vec<(usize, usize)> = vec![];
+
+        for (face_idx, face) in self.faces.iter().enumerate() {
+            for k in 0..face.len() {
+                let (a, b) = (face[k], face[(k + 1) % face.len()]);
+                let key = (a.min(b), a.max(b));
+
+                let edge = *edges.entry(key).or_insert_with(|| {
+                    edge_faces.push(vec![]);
+                    edge_vertices.push(key);
+                    edge_vertices.len() - 1
+                });
+
+                edge_faces[edge].push(face_idx);
+            }
+        }
+
+        let edge_points: Vec<Vec3> = edge_vertices.iter().zip(edge_faces.iter())
+            .map(|((a, b), faces)| {
+                let midpoint = (self.positions[*a] + self.positions[*b]) / 2.0;
+
+                if faces.len() == 2 {
+                    (midpoint + (face_points[faces[0]] + face_points[faces[1]]) / 2.0) / 2.0
+                }
+                else {
+                    midpoint
+                }
+            })
+            .collect();
+
+        let mut vertex_faces: Vec<Vec<usize>> = vec![vec![]; vertex_count];
+        let mut vertex_edges: Vec<Vec<usize>> = vec![vec![]; vertex_count];
+
+        for (face_idx, face) in self.faces.iter().enumerate() {
+            for idx in face.iter() {
+                vertex_faces[*idx].push(face_idx);
+            }
+        }
+
+        for (edge_idx, (a, b)) in edge_vertices.iter().enumerate() {
+            vertex_edges[*a].push(edge_idx);
+            vertex_edges[*b].push(edge_idx);
+        }
+
+        let mut positions: Vec<Vec3> = (0..vertex_count)
+            .map(|idx| {
+                let point = self.positions[idx];
+                let boundary: Vec<usize> = vertex_edges[idx].iter()
+                    .filter(|edge| edge_faces[**edge].len() != 2)
+                    .cloned()
+                    .collect();
+
+                if vertex_faces[idx].is_empty() {
+                    point
+                }
+                else if !boundary.is_empty() {
+                    if boundary.len() != 2 {
+                        return point;
+                    }
+
+                    let other = |edge: usize| {
+                        let (a, b) = edge_vertices[edge];
+                        self.positions[if a == idx { b } else { a }]
+                    };
+
+                    point * 0.75 + (other(boundary[0]) + other(boundary[1])) * 0.125
+                }
+                else {
+                    let n = vertex_faces[idx].len() as Float;
+                    let faces = vertex_faces[idx].iter().fold(Vec3::default(), |acc, face| acc + face_points[*face]) / n;
+                    let edges = vertex_edges[idx].iter().fold(Vec3::default(), |acc, edge| {
+                        let (a, b) = edge_vertices[*edge];
+                        acc + (self.positions[a] + self.positions[b]) / 2.0
+                    }) / vertex_edges[idx].len() as Float;
+
+                    (faces + edges * 2.0 + point * (n - 3.0)) / n
+                }
+            })
+            .collect();
+
+        let edge_base = positions.len();
+        positions.extend(edge_points);
+        let face_base = positions.len();
+        positions.extend(face_points);
+
+        let mut faces = vec![];
+        for (face_idx, face) in self.faces.iter().enumerate() {
+            let count = face.len();
+            let edge_point = |a: usize, b: usize| edge_base + edges[&(a.min(b), a.max(b))];
+
+            for k in 0..count {
+                let previous = face[(k + count - 1) % count];
+                let current = face[k];
+                let next = face[(k + 1) % count];
+
+                faces.push(vec![
+                    current,
+                    edge_point(current, next),
+                    face_base + face_idx,
+                    edge_point(previous, current)
+                ]);
+            }
+        }
+
+        Self {
+            positions,
+            faces
+        }
+    }
+
+    pub fn to_mesh(&self, material: Rc<dyn Material>) -> TriangleMesh {
+        let indices = self.faces.iter()
+            .flat_map(|face| (1..face.len() - 1).map(move |k| [face[0], face[k], face[k + 1]]))
+            .collect();
+
+        let mut data = MeshData::new(self.positions.clone(), None, None, indices, material);
+        data.normals = Some(data.smooth_normals());
+
+        TriangleMesh::new(data)
+    }
+}
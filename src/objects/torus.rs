@@ -0,0 +1,92 @@
+use super::hittable::{HitRecord, Hittable};
+use super::material::material::Material;
+use super::roots::solve_quartic;
+use super::bounding::BoundingBox;
+use super::vec3::Vec3;
+use super::ray::Ray;
+use super::Float;
+use super::consts::PI;
+
+use std::rc::Rc;
+
+pub struct Torus {
+    center: Vec3,
+    major_radius: Float,
+    minor_radius: Float,
+    material: Rc<dyn Material>
+}
+
+impl Torus {
+    pub fn new(center: Vec3, major_radius: Float, minor_radius: Float, material: Rc<dyn Material>) -> Self {
+        Self {
+            center,
+            major_radius,
+            minor_radius,
+            material
+        }
+    }
+
+    fn get_hit_record(&self, ray: &Ray, time: Float) -> HitRecord {
+        let point = ray.at(time);
+        let local = point - self.center;
+        let ring = Vec3::new(local.x, 0.0, local.z).unit_vector() * self.major_radius;
+        let (u, v) = self.get_uv(local);
+
+        HitRecord::new(
+            ray,
+            point,
+            (local - ring) / self.minor_radius,
+            time,
+            u,
+            v,
+            self.material.clone()
+        )
+    }
+
+    fn get_uv(&self, point: Vec3) -> (Float, Float) {
+        let phi = point.z.atan2(point.x);
+        let theta = point.y.atan2((point.x * point.x + point.z * point.z).sqrt() - self.major_radius);
+
+        (1.0 - (phi + PI) / (2.0 * PI), (theta + PI) / (2.0 * PI))
+    }
+}
+
+impl Hittable for Torus {
+    fn hit(&self, ray: &Ray, tmin: Float, tmax: Float) -> Option<HitRecord> {
+        let length = ray.direction.length();
+        let origin = ray.origin - self.center;
+        let direction = ray.direction / length;
+
+        let major2 = self.major_radius * self.major_radius;
+        let minor2 = self.minor_radius * self.minor_radius;
+        let od = Vec3::dot_product(origin, direction);
+        let e = origin.squared_length() - major2 - minor2;
+
+        let roots = solve_quartic(
+            1.0,
+            4.0 * od,
+            2.0 * e + 4.0 * od * od + 4.0 * major2 * direction.y * direction.y,
+            4.0 * e * od + 8.0 * major2 * origin.y * direction.y,
+            e * e - 4.0 * major2 * (minor2 - origin.y * origin.y)
+        );
+
+        for root in roots {
+            let time = root / length;
+
+            if time < tmax && time > tmin {
+                return Some(self.get_hit_record(ray, time));
+            }
+        }
+
+        None
+    }
+
+    fn bounding_box(&self, _t0: Float, _t1: Float) -> Option<BoundingBox> {
+        let extent = self.major_radius + self.minor_radius;
+
+        Some(BoundingBox::new(
+            self.center - Vec3::new(extent, self.minor_radius, extent),
+            self.center + Vec3::new(extent, self.minor_radius, extent)
+        ))
+    }
+}
@@ -1,6 +1,7 @@
 use super::utils::color::Color;
 use super::utils::vec3::Vec3;
 use super::texture::Texture;
+use super::utils::Float;
 
 use std::rc::Rc;
 
@@ -18,15 +19,20 @@ impl CheckerTexture {
     }
 }
 
-impl Texture for CheckerTexture {
-    fn color(&self, u: f64, v: f64, point: &Vec3) -> Color {
+impl CheckerTexture {
+    fn pick(&self, point: &Vec3) -> &Rc<dyn Texture> {
         let sin = (10.0 * point.x).sin() * (10.0 * point.y).sin() * (10.0 * point.z).sin();
 
-        if sin < 0.0 {
-            self.odd.color(u, v, point)
-        }
-        else {
-            self.even.color(u, v, point)
-        }
+        if sin < 0.0 { &self.odd } else { &self.even }
+    }
+}
+
+impl Texture for CheckerTexture {
+    fn color(&self, u: Float, v: Float, point: &Vec3) -> Color {
+        self.pick(point).color(u, v, point)
+    }
+
+    fn color_with_normal(&self, u: Float, v: Float, point: &Vec3, normal: &Vec3) -> Color {
+        self.pick(point).color_with_normal(u, v, point, normal)
     }
 }
\ No newline at end of file
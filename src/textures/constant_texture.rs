@@ -1,6 +1,7 @@
 use super::utils::color::Color;
 use super::utils::vec3::Vec3;
 use super::texture::Texture;
+use super::utils::Float;
 
 pub struct ConstantTexture {
     color: Color
@@ -15,7 +16,7 @@ impl ConstantTexture {
 }
 
 impl Texture for ConstantTexture {
-    fn color(&self, _u: f64, _v: f64, _point: &Vec3) -> Color {
+    fn color(&self, _u: Float, _v: Float, _point: &Vec3) -> Color {
         self.color
     }
 }
\ No newline at end of file
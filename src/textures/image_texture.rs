@@ -1,6 +1,7 @@
 use super::texture::Texture;
 use super::utils::color::Color;
 use super::utils::vec3::Vec3;
+use super::utils::Float;
 
 use num::clamp;
 
@@ -24,9 +25,9 @@ impl ImageTexture {
 }
 
 impl Texture for ImageTexture {
-    fn color(&self, u: f64, v: f64, _point: &Vec3) -> Color {
-        let mut i = (u * self.width as f64) as usize;
-        let mut j = ((1.0 - v) * self.height as f64) as usize;
+    fn color(&self, u: Float, v: Float, _point: &Vec3) -> Color {
+        let mut i = (u * self.width as Float) as usize;
+        let mut j = ((1.0 - v) * self.height as Float) as usize;
 
         i = clamp(i, 0, self.width - 1);
         j = clamp(j, 0, self.height - 1);
@@ -34,9 +35,9 @@ impl Texture for ImageTexture {
         let rgb = self.image.get_pixel(i as u32, j as u32).to_rgb();
 
         Color::new(
-            rgb[0] as f64 / 255.0,
-            rgb[1] as f64 / 255.0,
-            rgb[2] as f64 / 255.0,
+            rgb[0] as Float / 255.0,
+            rgb[1] as Float / 255.0,
+            rgb[2] as Float / 255.0,
         )
     }
 }
\ No newline at end of file
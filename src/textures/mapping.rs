@@ -0,0 +1,106 @@
+use super::utils::quaternion::Quaternion;
+use super::utils::color::Color;
+use super::utils::vec3::Vec3;
+use super::texture::Texture;
+use super::utils::Float;
+use super::utils::consts::PI;
+
+use std::rc::Rc;
+
+pub enum Projection {
+    Spherical,
+    //around the local y axis, v repeats every unit of height
+    Cylindrical,
+    //along the local z axis, u and v repeat every unit
+    Planar,
+    //three planar projections blended by the normal raised to the sharpness
+    Triplanar(Float)
+}
+
+pub struct TextureMapping {
+    texture: Rc<dyn Texture>,
+    projection: Projection,
+    center: Vec3,
+    rotation: Quaternion,
+    scale: Vec3
+}
+
+impl TextureMapping {
+    pub fn new(texture: Rc<dyn Texture>, projection: Projection, center: Vec3, rotation: Quaternion, scale: Vec3) -> Self {
+        Self {
+            texture,
+            projection,
+            center,
+            rotation: rotation.normalize(),
+            scale
+        }
+    }
+
+    pub fn spherical(texture: Rc<dyn Texture>, center: Vec3) -> Self {
+        Self::new(texture, Projection::Spherical, center, Quaternion::identity(), Vec3::new(1.0, 1.0, 1.0))
+    }
+
+    pub fn cylindrical(texture: Rc<dyn Texture>, center: Vec3, height: Float) -> Self {
+        Self::new(texture, Projection::Cylindrical, center, Quaternion::identity(), Vec3::new(1.0, height, 1.0))
+    }
+
+    pub fn planar(texture: Rc<dyn Texture>, center: Vec3, size: Float) -> Self {
+        Self::new(texture, Projection::Planar, center, Quaternion::identity(), Vec3::new(size, size, size))
+    }
+
+    pub fn triplanar(texture: Rc<dyn Texture>, center: Vec3, size: Float, sharpness: Float) -> Self {
+        Self::new(texture, Projection::Triplanar(sharpness), center, Quaternion::identity(), Vec3::new(size, size, size))
+    }
+
+    fn to_local(&self, point: &Vec3) -> Vec3 {
+        self.rotation.conjugate().rotate(*point - self.center) / self.scale
+    }
+
+    fn angular(local: Vec3) -> Float {
+        1.0 - (local.z.atan2(local.x) + PI) / (2.0 * PI)
+    }
+
+    fn project(&self, local: Vec3, normal: Vec3, point: &Vec3) -> Color {
+        match self.projection {
+            Projection::Spherical => {
+                let direction = local.unit_vector();
+                let v = (num::clamp(direction.y, -1.0, 1.0).asin() + PI / 2.0) / PI;
+
+                self.texture.color(Self::angular(direction), v, point)
+            },
+            Projection::Cylindrical => self.texture.color(Self::angular(local), local.y.rem_euclid(1.0), point),
+            Projection::Planar => self.texture.color(local.x.rem_euclid(1.0), local.y.rem_euclid(1.0), point),
+            Projection::Triplanar(sharpness) => {
+                let weights = Vec3::new(
+                    normal.x.abs().powf(sharpness),
+                    normal.y.abs().powf(sharpness),
+                    normal.z.abs().powf(sharpness)
+                );
+                let total = weights.x + weights.y + weights.z;
+
+                if total <= 0.0 {
+                    return Color::default();
+                }
+
+                self.texture.color(local.z.rem_euclid(1.0), local.y.rem_euclid(1.0), point) * (weights.x / total)
+                    + self.texture.color(local.x.rem_euclid(1.0), local.z.rem_euclid(1.0), point) * (weights.y / total)
+                    + self.texture.color(local.x.rem_euclid(1.0), local.y.rem_euclid(1.0), point) * (weights.z / total)
+            }
+        }
+    }
+}
+
+impl Texture for TextureMapping {
+    //without a normal the triplanar weights use the direction from the center
+    fn color(&self, _u: Float, _v: Float, point: &Vec3) -> Color {
+        let local = self.to_local(point);
+        self.project(local, local, point)
+    }
+
+    fn color_with_normal(&self, _u: Float, _v: Float, point: &Vec3, normal: &Vec3) -> Color {
+        let local = self.to_local(point);
+        let normal = self.rotation.conjugate().rotate(*normal) * self.scale;
+
+        self.project(local, normal, point)
+    }
+}
@@ -3,11 +3,13 @@ mod constant_texture;
 mod checker_texture;
 mod image_texture;
 mod perlin;
+mod mapping;
 
 use super::utils;
 
 pub use constant_texture::ConstantTexture;
 pub use checker_texture::CheckerTexture;
 pub use image_texture::ImageTexture;
-pub use perlin::NoiseTexture;
+pub use perlin::{NoiseTexture, Perlin};
+pub use mapping::{Projection, TextureMapping};
 pub use texture::Texture;
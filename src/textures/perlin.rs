@@ -1,18 +1,25 @@
 use super::texture::Texture;
 use super::utils::color::Color;
 use super::utils::vec3::Vec3;
+use super::utils::Float;
 
 use rand::Rng;
 
 const POINT_COUNT: usize = 256;
 
-struct Perlin {
+pub struct Perlin {
     rnd: Vec<Vec3>,
     x: Vec<usize>,
     y: Vec<usize>,
     z: Vec<usize>,
 }
 
+impl Default for Perlin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Perlin {
     pub fn new() -> Self {
         Self {
@@ -25,7 +32,7 @@ impl Perlin {
         }
     }
 
-    pub fn turbulence(&self, point: &Vec3, depth: usize) -> f64 {
+    pub fn turbulence(&self, point: &Vec3, depth: usize) -> Float {
         let mut acc = 0.0;
         let mut tmp_point = *point;
         let mut weight = 1.0;
@@ -39,7 +46,8 @@ impl Perlin {
         acc.abs()
     }
 
-    pub fn noise(&self, point: &Vec3) -> f64 {
+    #[allow(clippy::needless_range_loop)]
+    pub fn noise(&self, point: &Vec3) -> Float {
         let i = point.x.floor() as usize;
         let j = point.y.floor() as usize;
         let k = point.z.floor() as usize;
@@ -62,7 +70,8 @@ impl Perlin {
         Self::perlin_interpolation(&c, u, v, w)
     }
 
-    fn perlin_interpolation(c: &[[[Vec3; 2]; 2]; 2], u: f64, v: f64, w: f64) -> f64 {
+    #[allow(clippy::needless_range_loop)]
+    fn perlin_interpolation(c: &[[[Vec3; 2]; 2]; 2], u: Float, v: Float, w: Float) -> Float {
         let uu = u * u * (3.0 - 2.0 * u);
         let vv = v * v * (3.0 - 2.0 * v);
         let ww = w * w * (3.0 - 2.0 * w);
@@ -71,10 +80,10 @@ impl Perlin {
         for i in 0..2 {
             for j in 0..2 {
                 for k in 0..2 {
-                    let weight = Vec3::new(u - i as f64, v - j as f64, w - k as f64);
-                    acc += (i as f64 * uu + (1.0 - i as f64) * (1.0 - uu))
-                         * (j as f64 * vv + (1.0 - j as f64) * (1.0 - vv))
-                         * (k as f64 * ww + (1.0 - k as f64) * (1.0 - ww))
+                    let weight = Vec3::new(u - i as Float, v - j as Float, w - k as Float);
+                    acc += (i as Float * uu + (1.0 - i as Float) * (1.0 - uu))
+                         * (j as Float * vv + (1.0 - j as Float) * (1.0 - vv))
+                         * (k as Float * ww + (1.0 - k as Float) * (1.0 - ww))
                          * Vec3::dot_product(c[i][j][k], weight);
                 }
             }
@@ -84,31 +93,29 @@ impl Perlin {
     }
 
     fn generate_permutation() -> Vec<usize> {
-        let mut perm: Vec<usize> = (0..POINT_COUNT).map(|i|{ i }).collect();
+        let mut perm: Vec<usize> = (0..POINT_COUNT).collect();
 
         Self::permutate(&mut perm);
 
         perm
     }
 
-    fn permutate(arr: &mut Vec<usize>) {
+    fn permutate(arr: &mut [usize]) {
         let mut rng = rand::thread_rng();
         for i in (1..POINT_COUNT).rev() {
             let j = rng.gen_range(0, i);
-            let tmp = arr[j];
-            arr[j] = arr[i];
-            arr[i] = tmp;
+            arr.swap(i, j);
         }
     }
 }
 
 pub struct NoiseTexture {
     perlin: Perlin,
-    scale: f64,
+    scale: Float,
 }
 
 impl NoiseTexture {
-    pub fn new(scale: f64) -> Self {
+    pub fn new(scale: Float) -> Self {
         Self {
             perlin: Perlin::new(),
             scale,
@@ -117,7 +124,7 @@ impl NoiseTexture {
 }
 
 impl Texture for NoiseTexture {
-    fn color(&self, _u: f64, _v: f64, point: &Vec3) -> Color {
+    fn color(&self, _u: Float, _v: Float, point: &Vec3) -> Color {
         Color::new(1.0, 1.0, 1.0) * 0.5 * (1.0 + (self.scale * point.z + self.perlin.turbulence(point, 7) * 10.0).sin())
     }
 }
\ No newline at end of file
@@ -1,6 +1,12 @@
 use super::utils::color::Color;
 use super::utils::vec3::Vec3;
+use super::utils::Float;
 
 pub trait Texture {
-    fn color(&self, u: f64, v: f64, point: &Vec3) -> Color;
+    fn color(&self, u: Float, v: Float, point: &Vec3) -> Color;
+
+    //for textures that also depend on the surface orientation
+    fn color_with_normal(&self, u: Float, v: Float, point: &Vec3, _normal: &Vec3) -> Color {
+        self.color(u, v, point)
+    }
 }
\ No newline at end of file
@@ -1,3 +1,5 @@
+use super::Float;
+
 use std::ops::{Mul, Div, Add, Sub};
 
 use rand::distributions::{Distribution, Uniform}; 
@@ -5,13 +7,13 @@ use num::clamp;
 
 #[derive(Debug, Default, Copy)]
 pub struct Color {
-    pub r: f64,
-    pub g: f64,
-    pub b: f64,
+    pub r: Float,
+    pub g: Float,
+    pub b: Float,
 }
 
 impl Color {
-    pub fn new(r: f64, g: f64, b: f64) -> Self {
+    pub fn new(r: Float, g: Float, b: Float) -> Self {
         Self {
             r,
             g,
@@ -39,7 +41,7 @@ impl Color {
     }
 
     pub fn to_rgb_with_samples(&self, samples: i32) -> (u8, u8, u8) {
-        let scale = 1.0 / samples as f64;
+        let scale = 1.0 / samples as Float;
         let r = (255.0 * clamp((self.r * scale).sqrt(), 0.0, 1.0)) as u8;
         let g = (255.0 * clamp((self.g * scale).sqrt(), 0.0, 1.0)) as u8;
         let b = (255.0 * clamp((self.b * scale).sqrt(), 0.0, 1.0)) as u8;
@@ -102,10 +104,10 @@ type Output = Self;
     }
 }
 
-impl Mul<f64> for Color {
+impl Mul<Float> for Color {
 type Output = Self;
 
-    fn mul(self, other: f64) -> Self {
+    fn mul(self, other: Float) -> Self {
         Self {
             r: self.r * other,
             g: self.g * other,
@@ -114,10 +116,10 @@ type Output = Self;
     }
 }
 
-impl Div<f64> for Color {
+impl Div<Float> for Color {
 type Output = Self;
 
-    fn div(self, other: f64) -> Self {
+    fn div(self, other: Float) -> Self {
         Self {
             r: self.r / other,
             g: self.g / other,
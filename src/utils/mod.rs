@@ -1,11 +1,43 @@
 pub mod color;
 pub mod vec3;
 pub mod ray;
+pub mod roots;
+pub mod quaternion;
 
-pub fn min_f64(a: f64, b: f64) -> f64 {
+//the precision of all the math, double unless the f32 feature is on
+#[cfg(not(feature = "f32"))]
+pub type Float = f64;
+#[cfg(feature = "f32")]
+pub type Float = f32;
+
+#[cfg(not(feature = "f32"))]
+pub use std::f64::consts;
+#[cfg(feature = "f32")]
+pub use std::f32::consts;
+
+//pbrt's bound on the relative error of n rounded operations in the current precision
+pub const fn gamma(n: u32) -> Float {
+    let error = n as Float * Float::EPSILON / 2.0;
+    error / (1.0 - error)
+}
+
+pub fn min_float(a: Float, b: Float) -> Float {
     if a <= b { a } else { b }
 }
 
-pub fn max_f64(a: f64, b: f64) -> f64 {
+pub fn max_float(a: Float, b: Float) -> Float {
     if a >= b { a } else { b }
+}
+
+//rounds outwards so single precision bounds still contain the full precision ones
+#[allow(clippy::unnecessary_cast)]
+pub fn f32_below(x: Float) -> f32 {
+    let result = x as f32;
+    if result as Float > x { result.next_down() } else { result }
+}
+
+#[allow(clippy::unnecessary_cast)]
+pub fn f32_above(x: Float) -> f32 {
+    let result = x as f32;
+    if (result as Float) < x { result.next_up() } else { result }
 }
\ No newline at end of file
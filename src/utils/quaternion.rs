@@ -0,0 +1,93 @@
+use super::vec3::Vec3;
+use super::Float;
+
+#[derive(Debug, Copy, Clone)]
+pub struct Quaternion {
+    pub w: Float,
+    pub x: Float,
+    pub y: Float,
+    pub z: Float,
+}
+
+impl Default for Quaternion {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Quaternion {
+    pub fn new(w: Float, x: Float, y: Float, z: Float) -> Self {
+        Self {
+            w,
+            x,
+            y,
+            z
+        }
+    }
+
+    pub fn identity() -> Self {
+        Self::new(1.0, 0.0, 0.0, 0.0)
+    }
+
+    pub fn from_axis_angle(axis: Vec3, angle: Float) -> Self {
+        let axis = axis.unit_vector();
+        let (sin, cos) = (angle.to_radians() / 2.0).sin_cos();
+
+        Self::new(cos, axis.x * sin, axis.y * sin, axis.z * sin)
+    }
+
+    pub fn dot(left: Self, right: Self) -> Float {
+        left.w * right.w + left.x * right.x + left.y * right.y + left.z * right.z
+    }
+
+    pub fn normalize(&self) -> Self {
+        let length = Self::dot(*self, *self).sqrt();
+        Self::new(self.w / length, self.x / length, self.y / length, self.z / length)
+    }
+
+    pub fn conjugate(&self) -> Self {
+        Self::new(self.w, -self.x, -self.y, -self.z)
+    }
+
+    //angle in radians swept when interpolating between the two rotations
+    pub fn angle_between(left: Self, right: Self) -> Float {
+        2.0 * Self::dot(left, right).abs().min(1.0).acos()
+    }
+
+    pub fn slerp(from: Self, to: Self, t: Float) -> Self {
+        let mut to = to;
+        let mut cos = Self::dot(from, to);
+
+        if cos < 0.0 {
+            to = Self::new(-to.w, -to.x, -to.y, -to.z);
+            cos = -cos;
+        }
+
+        if cos > 0.9995 {
+            return Self::new(
+                from.w + (to.w - from.w) * t,
+                from.x + (to.x - from.x) * t,
+                from.y + (to.y - from.y) * t,
+                from.z + (to.z - from.z) * t
+            ).normalize();
+        }
+
+        let theta = cos.acos();
+        let a = ((1.0 - t) * theta).sin() / theta.sin();
+        let b = (t * theta).sin() / theta.sin();
+
+        Self::new(
+            from.w * a + to.w * b,
+            from.x * a + to.x * b,
+            from.y * a + to.y * b,
+            from.z * a + to.z * b
+        )
+    }
+
+    pub fn rotate(&self, v: Vec3) -> Vec3 {
+        let u = Vec3::new(self.x, self.y, self.z);
+        let t = Vec3::cross_product(u, v) * 2.0;
+
+        v + t * self.w + Vec3::cross_product(u, t)
+    }
+}
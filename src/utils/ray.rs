@@ -1,14 +1,15 @@
 use super::vec3::Vec3;
 use super::color::Color;
+use super::Float;
 
 pub struct Ray {
     pub origin: Vec3,
     pub direction: Vec3,
-    pub time: f64
+    pub time: Float
 }
 
 impl Ray {
-    pub fn new(origin: Vec3, direction: Vec3, time: f64) -> Self {
+    pub fn new(origin: Vec3, direction: Vec3, time: Float) -> Self {
         Self {
             origin,
             direction,
@@ -16,7 +17,7 @@ impl Ray {
         }
     }
 
-    pub fn at(&self, time: f64) -> Vec3 {
+    pub fn at(&self, time: Float) -> Vec3 {
         self.origin + self.direction * time
     }
 }
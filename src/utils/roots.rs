@@ -0,0 +1,189 @@
+use super::Float;
+use super::consts::PI;
+
+//below this a coefficient counts as zero, single precision needs a wider band or rays along the torus lose their roots
+#[cfg(not(feature = "f32"))]
+const EPSILON: Float = 1e-9;
+#[cfg(feature = "f32")]
+const EPSILON: Float = 1e-6;
+
+fn is_zero(x: Float) -> bool {
+    x.abs() < EPSILON
+}
+
+pub fn solve_quadratic(a: Float, b: Float, c: Float) -> Vec<Float> {
+    if is_zero(a) {
+        return if is_zero(b) { vec![] } else { vec![-c / b] };
+    }
+
+    let p = b / (2.0 * a);
+    let q = c / a;
+    let discriminant = p * p - q;
+
+    if is_zero(discriminant) {
+        vec![-p]
+    }
+    else if discriminant < 0.0 {
+        vec![]
+    }
+    else {
+        let root = discriminant.sqrt();
+        vec![-root - p, root - p]
+    }
+}
+
+pub fn solve_cubic(a: Float, b: Float, c: Float, d: Float) -> Vec<Float> {
+    if is_zero(a) {
+        return solve_quadratic(b, c, d);
+    }
+
+    let aa = b / a;
+    let bb = c / a;
+    let cc = d / a;
+
+    let sq_a = aa * aa;
+    let p = (-sq_a / 3.0 + bb) / 3.0;
+    let q = (2.0 / 27.0 * aa * sq_a - aa * bb / 3.0 + cc) / 2.0;
+    let cb_p = p * p * p;
+    let discriminant = q * q + cb_p;
+
+    let mut result = if is_zero(discriminant) {
+        if is_zero(q) {
+            vec![0.0]
+        }
+        else {
+            let u = (-q).cbrt();
+            vec![2.0 * u, -u]
+        }
+    }
+    else if discriminant < 0.0 {
+        let phi = (-q / (-cb_p).sqrt()).acos() / 3.0;
+        let t = 2.0 * (-p).sqrt();
+        vec![t * phi.cos(), -t * (phi + PI / 3.0).cos(), -t * (phi - PI / 3.0).cos()]
+    }
+    else {
+        let root = discriminant.sqrt();
+        vec![(root - q).cbrt() - (root + q).cbrt()]
+    };
+
+    for x in result.iter_mut() {
+        *x -= aa / 3.0;
+    }
+
+    result.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    result
+}
+
+pub fn solve_quartic(a: Float, b: Float, c: Float, d: Float, e: Float) -> Vec<Float> {
+    if is_zero(a) {
+        return solve_cubic(b, c, d, e);
+    }
+
+    let aa = b / a;
+    let bb = c / a;
+    let cc = d / a;
+    let dd = e / a;
+
+    let sq_a = aa * aa;
+    let p = -3.0 / 8.0 * sq_a + bb;
+    let q = sq_a * aa / 8.0 - aa * bb / 2.0 + cc;
+    let r = -3.0 / 256.0 * sq_a * sq_a + sq_a * bb / 16.0 - aa * cc / 4.0 + dd;
+
+    let mut result = if is_zero(r) {
+        let mut roots = solve_cubic(1.0, 0.0, p, q);
+        roots.push(0.0);
+        roots
+    }
+    else {
+        let z = *solve_cubic(1.0, -p / 2.0, -r, r * p / 2.0 - q * q / 8.0).last().unwrap();
+
+        let u = z * z - r;
+        let v = 2.0 * z - p;
+
+        let u = if is_zero(u) { 0.0 } else if u > 0.0 { u.sqrt() } else { return vec![] };
+        let v = if is_zero(v) { 0.0 } else if v > 0.0 { v.sqrt() } else { return vec![] };
+
+        let mut roots = solve_quadratic(1.0, if q < 0.0 { -v } else { v }, z - u);
+        roots.extend(solve_quadratic(1.0, if q < 0.0 { v } else { -v }, z + u));
+        roots
+    };
+
+    for x in result.iter_mut() {
+        *x -= aa / 4.0;
+
+        for _ in 0..2 {
+            let f = (((a * *x + b) * *x + c) * *x + d) * *x + e;
+            let df = ((4.0 * a * *x + 3.0 * b) * *x + 2.0 * c) * *x + d;
+
+            if is_zero(df) {
+                break;
+            }
+            *x -= f / df;
+        }
+    }
+
+    result.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    result
+}
+
+fn evaluate(coefficients: &[Float], x: Float) -> Float {
+    coefficients.iter().fold(0.0, |acc, coefficient| acc * x + coefficient)
+}
+
+//real roots in [low, high] of any degree, coefficients from the highest power down
+//the derivative's roots split the range into monotonic pieces that each cross zero at most once
+pub fn solve_polynomial(coefficients: &[Float], low: Float, high: Float) -> Vec<Float> {
+    let degree = coefficients.len().saturating_sub(1);
+
+    if degree == 0 {
+        return vec![];
+    }
+
+    let derivative: Vec<Float> = coefficients[..degree].iter().enumerate()
+        .map(|(idx, coefficient)| coefficient * (degree - idx) as Float)
+        .collect();
+
+    let mut bounds = vec![low];
+    bounds.extend(solve_polynomial(&derivative, low, high));
+    bounds.push(high);
+
+    let mut result: Vec<Float> = vec![];
+
+    for piece in bounds.windows(2) {
+        let (mut a, mut b) = (piece[0], piece[1]);
+        let (fa, fb) = (evaluate(coefficients, a), evaluate(coefficients, b));
+
+        let root = if fa == 0.0 {
+            a
+        }
+        else if fb == 0.0 {
+            b
+        }
+        else if (fa < 0.0) != (fb < 0.0) {
+            //bisects until the midpoint can't move anymore
+            loop {
+                let mid = (a + b) / 2.0;
+
+                if mid <= a || mid >= b {
+                    break mid;
+                }
+
+                if (evaluate(coefficients, mid) < 0.0) == (fa < 0.0) {
+                    a = mid;
+                }
+                else {
+                    b = mid;
+                }
+            }
+        }
+        else {
+            continue;
+        };
+
+        if result.last().is_none_or(|last| *last < root) {
+            result.push(root);
+        }
+    }
+
+    result
+}
@@ -1,21 +1,21 @@
 use std::ops::{Add, Sub, Mul, Div, Neg, Index, IndexMut};
-use std::f64::consts::PI;
-use std::f64;
 
-use super::min_f64;
+use super::min_float;
+use super::Float;
+use super::consts::PI;
 
 use rand::distributions::{Uniform, Distribution};
 use rand::Rng;
 
 #[derive(Debug, Copy, Default)]
 pub struct Vec3 {
-    pub x: f64,
-    pub y: f64,
-    pub z: f64,
+    pub x: Float,
+    pub y: Float,
+    pub z: Float,
 }
 
 impl Vec3 {
-    pub fn new(x: f64, y: f64, z: f64) -> Self {
+    pub fn new(x: Float, y: Float, z: Float) -> Self {
         Self {
             x,
             y,
@@ -23,7 +23,7 @@ impl Vec3 {
         }
     }
 
-    pub fn random_range(from: f64, to: f64) -> Self {
+    pub fn random_range(from: Float, to: Float) -> Self {
         let mut rng = rand::thread_rng();
         let range = Uniform::from(from..to);
 
@@ -37,13 +37,13 @@ impl Vec3 {
     pub fn random_unit() -> Self {
         let mut rng = rand::thread_rng();
         let a = rng.gen_range(0.0, 2.0 * PI);
-        let z = rng.gen_range(-1.0f64, 1.0f64);
+        let z = rng.gen_range(-1.0 as Float, 1.0);
         let r = (1.0 - z * z).sqrt();
 
         Self {
             x: r * a.cos(),
             y: r * a.sin(),
-            z: z,
+            z,
         }
     }
 
@@ -72,15 +72,15 @@ impl Vec3 {
         }
     }
 
-    pub fn squared_length(&self) -> f64 {
+    pub fn squared_length(&self) -> Float {
         self.x * self.x + self.y * self.y + self.z * self.z
     }
 
-    pub fn length(&self) -> f64 {
+    pub fn length(&self) -> Float {
         self.squared_length().sqrt()
     }
 
-    pub fn dot_product(left: Self, right: Self) -> f64 {
+    pub fn dot_product(left: Self, right: Self) -> Float {
         left.x * right.x + left.y * right.y + left.z * right.z
     }
 
@@ -96,8 +96,8 @@ impl Vec3 {
         v - normal * 2.0 * Self::dot_product(v, normal)
     }
 
-    pub fn refract(v: Self, normal: Self, coeff: f64) -> Self {
-        let cos = min_f64(1.0, Self::dot_product(-v, normal));
+    pub fn refract(v: Self, normal: Self, coeff: Float) -> Self {
+        let cos = min_float(1.0, Self::dot_product(-v, normal));
         let r_parallel = (v + normal * cos) * coeff;
         let r_perpendicular = -normal * (1.0 - r_parallel.squared_length()).sqrt();
         
@@ -110,9 +110,9 @@ impl Vec3 {
 }
 
 impl Index<usize> for Vec3 {
-    type Output = f64;
+    type Output = Float;
 
-    fn index(&self, index: usize) -> &f64 {
+    fn index(&self, index: usize) -> &Float {
         match index {
             0 => &self.x,
             1 => &self.y,
@@ -123,7 +123,7 @@ impl Index<usize> for Vec3 {
 }
 
 impl IndexMut<usize> for Vec3 {
-    fn index_mut(&mut self, index: usize) -> &mut f64 {
+    fn index_mut(&mut self, index: usize) -> &mut Float {
         match index {
             0 => &mut self.x,
             1 => &mut self.y,
@@ -188,10 +188,10 @@ type Output = Self;
     }
 }
 
-impl Mul<f64> for Vec3 {
+impl Mul<Float> for Vec3 {
 type Output = Self;
 
-    fn mul(self, other: f64) -> Self {
+    fn mul(self, other: Float) -> Self {
         Self {
             x: self.x * other,
             y: self.y * other,
@@ -200,10 +200,10 @@ type Output = Self;
     }
 }
 
-impl Div<f64> for Vec3 {
+impl Div<Float> for Vec3 {
 type Output = Self;
 
-    fn div(self, other: f64) -> Self {
+    fn div(self, other: Float) -> Self {
         Self {
             x: self.x / other,
             y: self.y / other,
@@ -222,4 +222,29 @@ type Output = Self;
             z: -self.z,
         }
     }
-}
\ No newline at end of file
+}
+//single precision storage for big arrays like mesh vertices, widening back to a Vec3 is exact
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PackedVec3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32
+}
+
+impl From<Vec3> for PackedVec3 {
+    #[allow(clippy::unnecessary_cast)]
+    fn from(vector: Vec3) -> Self {
+        Self {
+            x: vector.x as f32,
+            y: vector.y as f32,
+            z: vector.z as f32
+        }
+    }
+}
+
+impl From<PackedVec3> for Vec3 {
+    #[allow(clippy::unnecessary_cast)]
+    fn from(vector: PackedVec3) -> Self {
+        Vec3::new(vector.x as Float, vector.y as Float, vector.z as Float)
+    }
+}